@@ -0,0 +1,48 @@
+CREATE TABLE IF NOT EXISTS roles
+(
+    name VARCHAR(50) PRIMARY KEY,
+    description TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS permissions
+(
+    name VARCHAR(50) PRIMARY KEY,
+    description TEXT NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS role_permissions
+(
+    role VARCHAR(50) NOT NULL REFERENCES roles (name) ON DELETE CASCADE,
+    permission VARCHAR(50) NOT NULL REFERENCES permissions (name) ON DELETE CASCADE,
+    PRIMARY KEY (role, permission)
+);
+
+CREATE TABLE IF NOT EXISTS user_roles
+(
+    user_id BIGINT NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    role VARCHAR(50) NOT NULL REFERENCES roles (name) ON DELETE CASCADE,
+    granted_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (user_id, role)
+);
+
+INSERT INTO roles (name, description)
+VALUES
+    ('admin', 'Full access to every account and role assignment'),
+    ('support', 'Can look up any account'),
+    ('viewer', 'Regular subscriber, only has access to their own account')
+ON CONFLICT DO NOTHING;
+
+INSERT INTO permissions (name, description)
+VALUES
+    ('users:read', 'Read any user account'),
+    ('users:write', 'Modify or delete any user account'),
+    ('roles:manage', 'Grant and revoke roles')
+ON CONFLICT DO NOTHING;
+
+INSERT INTO role_permissions (role, permission)
+VALUES
+    ('admin', 'users:read'),
+    ('admin', 'users:write'),
+    ('admin', 'roles:manage'),
+    ('support', 'users:read')
+ON CONFLICT DO NOTHING;
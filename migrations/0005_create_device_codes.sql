@@ -0,0 +1,19 @@
+CREATE TABLE IF NOT EXISTS device_codes
+(
+    id BIGSERIAL PRIMARY KEY,
+    device_code_hash CHAR(64) UNIQUE NOT NULL,
+    user_code CHAR(8) UNIQUE NOT NULL,
+    user_id BIGINT REFERENCES users (id) ON DELETE CASCADE,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending'
+        CHECK (status IN ('pending', 'approved', 'denied', 'consumed')),
+    interval_secs INTEGER NOT NULL,
+    last_polled_at TIMESTAMPTZ,
+    expires_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+
+-- counts `POST /device/verify` attempts so user codes cannot be guessed
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS user_code_attempts INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS user_code_locked_until TIMESTAMPTZ;
\ No newline at end of file
@@ -0,0 +1,24 @@
+CREATE TABLE IF NOT EXISTS devices
+(
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    model VARCHAR(100) NOT NULL,
+    firmware_version VARCHAR(50) NOT NULL,
+    os_version VARCHAR(50) NOT NULL,
+    app_version VARCHAR(50) NOT NULL,
+    last_seen_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS devices_user_id_idx ON devices (user_id);
+
+-- removing a device revokes the refresh tokens issued to it
+ALTER TABLE refresh_tokens
+    ADD COLUMN IF NOT EXISTS device_id BIGINT REFERENCES devices (id) ON DELETE CASCADE;
+
+-- device details sent by the TV when pairing, registered once the code is approved
+ALTER TABLE device_codes
+    ADD COLUMN IF NOT EXISTS model VARCHAR(100),
+    ADD COLUMN IF NOT EXISTS firmware_version VARCHAR(50),
+    ADD COLUMN IF NOT EXISTS os_version VARCHAR(50),
+    ADD COLUMN IF NOT EXISTS app_version VARCHAR(50);
@@ -0,0 +1,15 @@
+CREATE TABLE IF NOT EXISTS profiles
+(
+    id BIGSERIAL PRIMARY KEY,
+    user_id BIGINT NOT NULL REFERENCES users (id) ON DELETE CASCADE,
+    name VARCHAR(50) NOT NULL,
+    avatar VARCHAR(200),
+    is_kids BOOLEAN NOT NULL DEFAULT false,
+    language VARCHAR(10) NOT NULL DEFAULT 'en',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    UNIQUE (user_id, name)
+);
+
+-- removing a profile signs out the sessions that selected it
+ALTER TABLE refresh_tokens
+    ADD COLUMN IF NOT EXISTS profile_id BIGINT REFERENCES profiles (id) ON DELETE CASCADE;
@@ -0,0 +1,54 @@
+CREATE TABLE IF NOT EXISTS titles
+(
+    id BIGSERIAL PRIMARY KEY,
+    kind VARCHAR(10) NOT NULL CHECK (kind IN ('movie', 'series')),
+    name VARCHAR(200) NOT NULL,
+    synopsis TEXT NOT NULL DEFAULT '',
+    genres VARCHAR(50)[] NOT NULL DEFAULT '{}',
+    release_year INTEGER NOT NULL,
+    -- minimum viewer age, e.g. 0, 6, 12, 16 or 18
+    age_rating SMALLINT NOT NULL DEFAULT 0,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX IF NOT EXISTS titles_genres_idx ON titles USING GIN (genres);
+CREATE INDEX IF NOT EXISTS titles_release_year_idx ON titles (release_year);
+
+CREATE TABLE IF NOT EXISTS seasons
+(
+    id BIGSERIAL PRIMARY KEY,
+    title_id BIGINT NOT NULL REFERENCES titles (id) ON DELETE CASCADE,
+    number INTEGER NOT NULL,
+    name VARCHAR(200) NOT NULL,
+    release_year INTEGER,
+    UNIQUE (title_id, number)
+);
+
+CREATE TABLE IF NOT EXISTS episodes
+(
+    id BIGSERIAL PRIMARY KEY,
+    season_id BIGINT NOT NULL REFERENCES seasons (id) ON DELETE CASCADE,
+    number INTEGER NOT NULL,
+    name VARCHAR(200) NOT NULL,
+    synopsis TEXT NOT NULL DEFAULT '',
+    duration_secs INTEGER NOT NULL,
+    UNIQUE (season_id, number)
+);
+
+-- streams and artwork, attached to a movie or series, or to a single episode
+CREATE TABLE IF NOT EXISTS assets
+(
+    id BIGSERIAL PRIMARY KEY,
+    title_id BIGINT REFERENCES titles (id) ON DELETE CASCADE,
+    episode_id BIGINT REFERENCES episodes (id) ON DELETE CASCADE,
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('video', 'trailer', 'poster', 'backdrop')),
+    url TEXT NOT NULL,
+    mime_type VARCHAR(100) NOT NULL,
+    width INTEGER,
+    height INTEGER,
+    duration_secs INTEGER,
+    CHECK ((title_id IS NULL) <> (episode_id IS NULL))
+);
+
+CREATE INDEX IF NOT EXISTS assets_title_id_idx ON assets (title_id);
+CREATE INDEX IF NOT EXISTS assets_episode_id_idx ON assets (episode_id);
@@ -0,0 +1,16 @@
+-- one row per profile and movie, or per profile and episode
+CREATE TABLE IF NOT EXISTS watch_progress
+(
+    profile_id BIGINT NOT NULL REFERENCES profiles (id) ON DELETE CASCADE,
+    title_id BIGINT NOT NULL REFERENCES titles (id) ON DELETE CASCADE,
+    episode_id BIGINT REFERENCES episodes (id) ON DELETE CASCADE,
+    position_secs INTEGER NOT NULL,
+    duration_secs INTEGER NOT NULL,
+    completed BOOLEAN NOT NULL DEFAULT false,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS watch_progress_key
+    ON watch_progress (profile_id, title_id, (COALESCE(episode_id, 0)));
+CREATE INDEX IF NOT EXISTS watch_progress_recent_idx
+    ON watch_progress (profile_id, updated_at DESC);
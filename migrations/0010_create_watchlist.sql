@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS watchlist
+(
+    profile_id BIGINT NOT NULL REFERENCES profiles (id) ON DELETE CASCADE,
+    title_id BIGINT NOT NULL REFERENCES titles (id) ON DELETE CASCADE,
+    -- custom order chosen by the viewer, 1 is the first entry
+    position INTEGER NOT NULL,
+    added_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    PRIMARY KEY (profile_id, title_id),
+    -- deferrable so moves and deletes can shift positions within one statement
+    CONSTRAINT watchlist_position_key UNIQUE (profile_id, position) DEFERRABLE
+);
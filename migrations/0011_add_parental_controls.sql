@@ -0,0 +1,12 @@
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS parental_pin_hash TEXT,
+    ADD COLUMN IF NOT EXISTS pin_failed_attempts INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN IF NOT EXISTS pin_locked_until TIMESTAMPTZ;
+
+-- highest title age rating the profile may see, 18 allows everything
+ALTER TABLE profiles
+    ADD COLUMN IF NOT EXISTS max_age_rating SMALLINT NOT NULL DEFAULT 18;
+
+UPDATE profiles
+SET max_age_rating = 6
+WHERE is_kids;
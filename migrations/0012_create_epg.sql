@@ -0,0 +1,25 @@
+CREATE TABLE IF NOT EXISTS channels
+(
+    id BIGSERIAL PRIMARY KEY,
+    -- channel id used in XMLTV files, e.g. `bbc1.uk`
+    xmltv_id VARCHAR(200) NOT NULL UNIQUE,
+    name VARCHAR(200) NOT NULL,
+    icon TEXT
+);
+
+CREATE TABLE IF NOT EXISTS programs
+(
+    id BIGSERIAL PRIMARY KEY,
+    channel_id BIGINT NOT NULL REFERENCES channels (id) ON DELETE CASCADE,
+    starts_at TIMESTAMPTZ NOT NULL,
+    ends_at TIMESTAMPTZ NOT NULL,
+    title TEXT NOT NULL,
+    subtitle TEXT,
+    description TEXT,
+    category TEXT,
+    UNIQUE (channel_id, starts_at),
+    CHECK (ends_at > starts_at)
+);
+
+-- schedule windows are looked up by overlap with `tstzrange(starts_at, ends_at)`
+CREATE INDEX IF NOT EXISTS programs_schedule_idx ON programs USING GIST (tstzrange(starts_at, ends_at));
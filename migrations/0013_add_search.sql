@@ -0,0 +1,24 @@
+-- trigram matching for the fuzzy fallback of title and user search. Extensions are
+-- database wide, so it lives in public where every PG_SCHEMA can reach it.
+CREATE EXTENSION IF NOT EXISTS pg_trgm SCHEMA public;
+
+-- 'simple' keeps words unstemmed, so prefixes typed by the viewer match
+ALTER TABLE titles
+    ADD COLUMN IF NOT EXISTS search_vector TSVECTOR GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', name), 'A')
+            || setweight(to_tsvector('simple', synopsis), 'B')
+    ) STORED;
+
+CREATE INDEX IF NOT EXISTS titles_search_idx ON titles USING GIN (search_vector);
+CREATE INDEX IF NOT EXISTS titles_name_trgm_idx ON titles USING GIN (name public.gin_trgm_ops);
+
+-- lets /search find users for callers allowed to read them
+ALTER TABLE users
+    ADD COLUMN IF NOT EXISTS search_vector TSVECTOR GENERATED ALWAYS AS (
+        setweight(to_tsvector('simple', username), 'A')
+            || setweight(to_tsvector('simple', first_name || ' ' || last_name), 'B')
+    ) STORED;
+
+CREATE INDEX IF NOT EXISTS users_search_idx ON users USING GIN (search_vector);
+CREATE INDEX IF NOT EXISTS users_search_trgm_idx ON users
+    USING GIN ((username || ' ' || first_name || ' ' || last_name) public.gin_trgm_ops);
\ No newline at end of file
@@ -0,0 +1,6 @@
+INSERT INTO programs
+    (channel_id, starts_at, ends_at, title, subtitle, description, category)
+SELECT $1, *
+FROM UNNEST(
+    $2::TIMESTAMPTZ[], $3::TIMESTAMPTZ[], $4::TEXT[], $5::TEXT[], $6::TEXT[], $7::TEXT[]
+);
\ No newline at end of file
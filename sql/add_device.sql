@@ -0,0 +1,5 @@
+INSERT INTO devices
+    (user_id, model, firmware_version, os_version, app_version)
+VALUES
+    ($1, $2, $3, $4, $5)
+RETURNING $table_fields;
\ No newline at end of file
@@ -0,0 +1,4 @@
+INSERT INTO device_codes
+    (device_code_hash, user_code, interval_secs, expires_at, model, firmware_version, os_version, app_version)
+VALUES
+    ($1, $2, $3, now() + make_interval(secs => $4), $5, $6, $7, $8);
\ No newline at end of file
@@ -0,0 +1,5 @@
+INSERT INTO profiles
+    (user_id, name, avatar, is_kids, language, max_age_rating)
+VALUES
+    ($1, $2, $3, $4, $5, $6)
+RETURNING $table_fields;
\ No newline at end of file
@@ -0,0 +1,4 @@
+INSERT INTO refresh_tokens
+    (user_id, family, token_hash, expires_at, device_id, profile_id)
+VALUES
+    ($1, $2, $3, now() + make_interval(secs => $4), $5, $6);
\ No newline at end of file
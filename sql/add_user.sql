@@ -1,5 +1,5 @@
-INSERT INTO testing.users
-    (email, first_name, last_name, username)
+INSERT INTO users
+    (email, first_name, last_name, username, password_hash)
 VALUES
-    ($1, $2, $3, $4)
+    ($1, $2, $3, $4, $5)
 RETURNING $table_fields;
\ No newline at end of file
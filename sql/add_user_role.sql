@@ -0,0 +1,5 @@
+INSERT INTO user_roles
+    (user_id, role)
+VALUES
+    ($1, $2)
+ON CONFLICT DO NOTHING;
\ No newline at end of file
@@ -0,0 +1,12 @@
+-- titles above the age rating limit of the profile are not added
+INSERT INTO watchlist
+    (profile_id, title_id, position)
+SELECT profiles.id, titles.id, (
+    SELECT COALESCE(MAX(position), 0) + 1
+    FROM watchlist
+    WHERE profile_id = $1
+)
+FROM profiles
+JOIN titles ON titles.age_rating <= profiles.max_age_rating
+WHERE profiles.id = $1 AND titles.id = $2
+RETURNING $table_fields;
\ No newline at end of file
@@ -0,0 +1,3 @@
+UPDATE device_codes
+SET user_id = $2, status = $3
+WHERE user_code = $1 AND status = 'pending' AND expires_at > now();
\ No newline at end of file
@@ -0,0 +1,3 @@
+SELECT last_seen_at < now() - interval '1 minute' AS stale
+FROM devices
+WHERE id = $1 AND user_id = $2;
\ No newline at end of file
@@ -0,0 +1,8 @@
+SELECT COUNT(*)
+FROM users
+WHERE ($1::VARCHAR IS NULL OR username = $1)
+    AND ($2::VARCHAR IS NULL OR email ILIKE $2)
+    AND ($3::VARCHAR IS NULL
+        OR first_name ILIKE $3
+        OR last_name ILIKE $3
+        OR first_name || ' ' || last_name ILIKE $3);
\ No newline at end of file
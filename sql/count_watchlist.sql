@@ -0,0 +1,6 @@
+SELECT COUNT(*)
+FROM watchlist
+JOIN titles ON titles.id = watchlist.title_id
+JOIN profiles ON profiles.id = watchlist.profile_id
+WHERE watchlist.profile_id = $1
+    AND titles.age_rating <= profiles.max_age_rating;
\ No newline at end of file
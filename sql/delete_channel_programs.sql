@@ -0,0 +1,3 @@
+DELETE FROM programs
+WHERE channel_id = $1
+    AND tstzrange(starts_at, ends_at) && tstzrange($2, $3);
\ No newline at end of file
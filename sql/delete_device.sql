@@ -0,0 +1,2 @@
+DELETE FROM devices
+WHERE id = $1 AND user_id = $2;
\ No newline at end of file
@@ -0,0 +1,2 @@
+DELETE FROM device_codes
+WHERE expires_at < now() - interval '1 day';
\ No newline at end of file
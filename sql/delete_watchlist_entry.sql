@@ -0,0 +1,12 @@
+WITH removed AS (
+    DELETE FROM watchlist
+    WHERE profile_id = $1 AND title_id = $2
+    RETURNING position
+), shifted AS (
+    UPDATE watchlist
+    SET position = watchlist.position - 1
+    FROM removed
+    WHERE watchlist.profile_id = $1 AND watchlist.position > removed.position
+)
+SELECT COUNT(*) AS removed
+FROM removed;
\ No newline at end of file
@@ -0,0 +1,9 @@
+SELECT $table_fields,
+    NULL::TEXT AS highlight,
+    NULL::TEXT AS snippet
+FROM titles
+WHERE ($1 OPERATOR(public.<%) titles.name OR $1 OPERATOR(public.%) titles.name)
+    AND ($2::SMALLINT IS NULL OR titles.age_rating <= $2)
+ORDER BY public.word_similarity($1, titles.name) DESC, titles.name, titles.id
+LIMIT $3
+OFFSET $4;
\ No newline at end of file
@@ -0,0 +1,12 @@
+-- the expression matches users_search_trgm_idx
+SELECT $table_fields,
+    NULL::TEXT AS highlight
+FROM users
+WHERE ($1 OPERATOR(public.<%) (users.username || ' ' || users.first_name || ' ' || users.last_name)
+    OR $1 OPERATOR(public.%) (users.username || ' ' || users.first_name || ' ' || users.last_name))
+ORDER BY
+    public.word_similarity($1, users.username || ' ' || users.first_name || ' ' || users.last_name) DESC,
+    users.username,
+    users.id
+LIMIT $2
+OFFSET $3;
\ No newline at end of file
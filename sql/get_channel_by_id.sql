@@ -0,0 +1,3 @@
+SELECT $table_fields
+FROM channels
+WHERE id = $1;
\ No newline at end of file
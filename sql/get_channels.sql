@@ -0,0 +1,3 @@
+SELECT $table_fields
+FROM channels
+ORDER BY name, id;
\ No newline at end of file
@@ -0,0 +1,13 @@
+SELECT $progress_fields, $title_fields
+FROM (
+    SELECT DISTINCT ON (title_id) *
+    FROM watch_progress
+    WHERE profile_id = $1
+    ORDER BY title_id, updated_at DESC
+) AS watch_progress
+JOIN titles ON titles.id = watch_progress.title_id
+JOIN profiles ON profiles.id = watch_progress.profile_id
+WHERE NOT watch_progress.completed
+    AND titles.age_rating <= profiles.max_age_rating
+ORDER BY watch_progress.updated_at DESC
+LIMIT $2;
\ No newline at end of file
@@ -0,0 +1,14 @@
+SELECT
+    id,
+    user_id,
+    status,
+    model,
+    firmware_version,
+    os_version,
+    app_version,
+    expires_at <= now() AS expired,
+    last_polled_at IS NOT NULL
+        AND last_polled_at + make_interval(secs => interval_secs) > now() AS too_fast
+FROM device_codes
+WHERE device_code_hash = $1
+FOR UPDATE;
\ No newline at end of file
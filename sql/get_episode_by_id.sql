@@ -0,0 +1,6 @@
+SELECT $table_fields
+FROM episodes
+JOIN seasons ON seasons.id = episodes.season_id
+JOIN titles ON titles.id = seasons.title_id
+WHERE episodes.id = $1
+    AND ($2::SMALLINT IS NULL OR titles.age_rating <= $2);
\ No newline at end of file
@@ -0,0 +1,3 @@
+SELECT $table_fields
+FROM profiles
+WHERE id = $1 AND user_id = $2;
\ No newline at end of file
@@ -0,0 +1,5 @@
+SELECT $table_fields
+FROM programs
+WHERE channel_id = ANY($1)
+    AND tstzrange(starts_at, ends_at) && tstzrange($2, $3)
+ORDER BY channel_id, starts_at;
\ No newline at end of file
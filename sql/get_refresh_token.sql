@@ -0,0 +1,11 @@
+SELECT
+    id,
+    user_id,
+    device_id,
+    profile_id,
+    family,
+    revoked_at IS NOT NULL AS revoked,
+    expires_at <= now() AS expired
+FROM refresh_tokens
+WHERE token_hash = $1
+FOR UPDATE;
\ No newline at end of file
@@ -0,0 +1,10 @@
+SELECT roles.name, roles.description,
+    COALESCE(
+        array_agg(role_permissions.permission ORDER BY role_permissions.permission)
+            FILTER (WHERE role_permissions.permission IS NOT NULL),
+        '{}'
+    ) AS permissions
+FROM roles
+LEFT JOIN role_permissions ON role_permissions.role = roles.name
+GROUP BY roles.name
+ORDER BY roles.name;
\ No newline at end of file
@@ -0,0 +1,3 @@
+SELECT version, checksum, applied_at::TEXT AS applied_at
+FROM schema_migrations
+ORDER BY version;
\ No newline at end of file
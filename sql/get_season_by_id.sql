@@ -0,0 +1,5 @@
+SELECT $table_fields
+FROM seasons
+JOIN titles ON titles.id = seasons.title_id
+WHERE seasons.id = $1
+    AND ($2::SMALLINT IS NULL OR titles.age_rating <= $2);
\ No newline at end of file
@@ -0,0 +1,4 @@
+SELECT $table_fields
+FROM episodes
+WHERE season_id = $1
+ORDER BY number;
\ No newline at end of file
@@ -0,0 +1,4 @@
+SELECT $table_fields
+FROM assets
+WHERE title_id = $1
+ORDER BY kind, id;
\ No newline at end of file
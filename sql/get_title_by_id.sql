@@ -0,0 +1,4 @@
+SELECT $table_fields
+FROM titles
+WHERE id = $1
+    AND ($2::SMALLINT IS NULL OR age_rating <= $2);
\ No newline at end of file
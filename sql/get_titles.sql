@@ -0,0 +1,9 @@
+SELECT $table_fields
+FROM titles
+WHERE ($1::VARCHAR IS NULL OR genres @> ARRAY[$1::VARCHAR])
+    AND ($2::INTEGER IS NULL OR release_year = $2)
+    AND ($3::SMALLINT IS NULL OR age_rating <= $3)
+    AND ($4::VARCHAR IS NULL OR kind = $4)
+ORDER BY name, id
+LIMIT $5
+OFFSET $6;
\ No newline at end of file
@@ -1,3 +1,3 @@
 SELECT $table_fields
-FROM testing.users
+FROM users
 WHERE id = $1;
@@ -0,0 +1,3 @@
+SELECT $table_fields, users.password_hash
+FROM users
+WHERE username = $1;
\ No newline at end of file
@@ -0,0 +1,4 @@
+SELECT $table_fields
+FROM devices
+WHERE user_id = $1
+ORDER BY last_seen_at DESC, id DESC;
\ No newline at end of file
@@ -0,0 +1,6 @@
+SELECT EXISTS (
+    SELECT 1
+    FROM user_roles
+    JOIN role_permissions ON role_permissions.role = user_roles.role
+    WHERE user_roles.user_id = $1 AND role_permissions.permission = $2
+) AS granted;
\ No newline at end of file
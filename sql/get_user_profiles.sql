@@ -0,0 +1,4 @@
+SELECT $table_fields
+FROM profiles
+WHERE user_id = $1
+ORDER BY id;
\ No newline at end of file
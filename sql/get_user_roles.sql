@@ -0,0 +1,12 @@
+SELECT roles.name, roles.description,
+    COALESCE(
+        array_agg(role_permissions.permission ORDER BY role_permissions.permission)
+            FILTER (WHERE role_permissions.permission IS NOT NULL),
+        '{}'
+    ) AS permissions
+FROM user_roles
+JOIN roles ON roles.name = user_roles.role
+LEFT JOIN role_permissions ON role_permissions.role = roles.name
+WHERE user_roles.user_id = $1
+GROUP BY roles.name
+ORDER BY roles.name;
\ No newline at end of file
@@ -1,2 +1,13 @@
 SELECT $table_fields
-FROM testing.users;
+FROM users
+WHERE ($1::VARCHAR IS NULL OR username = $1)
+    AND ($2::VARCHAR IS NULL OR email ILIKE $2)
+    AND ($3::VARCHAR IS NULL
+        OR first_name ILIKE $3
+        OR last_name ILIKE $3
+        OR first_name || ' ' || last_name ILIKE $3)
+    AND ($4::BIGINT IS NULL OR id > $4)
+    AND ($5::BIGINT IS NULL OR id < $5)
+ORDER BY $order_by
+LIMIT $6
+OFFSET $7;
\ No newline at end of file
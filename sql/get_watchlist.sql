@@ -0,0 +1,9 @@
+SELECT $watchlist_fields, $title_fields
+FROM watchlist
+JOIN titles ON titles.id = watchlist.title_id
+JOIN profiles ON profiles.id = watchlist.profile_id
+WHERE watchlist.profile_id = $1
+    AND titles.age_rating <= profiles.max_age_rating
+ORDER BY $order_by
+LIMIT $2
+OFFSET $3;
\ No newline at end of file
@@ -0,0 +1,3 @@
+SELECT parental_pin_hash IS NOT NULL AS has_pin
+FROM users
+WHERE id = $1;
\ No newline at end of file
@@ -0,0 +1,4 @@
+SELECT id
+FROM profiles
+WHERE id = $1
+FOR NO KEY UPDATE;
\ No newline at end of file
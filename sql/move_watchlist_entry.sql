@@ -0,0 +1,20 @@
+WITH moved AS (
+    SELECT
+        position AS old_position,
+        LEAST(
+            GREATEST($3::INTEGER, 1),
+            (SELECT COUNT(*) FROM watchlist WHERE profile_id = $1)
+        )::INTEGER AS new_position
+    FROM watchlist
+    WHERE profile_id = $1 AND title_id = $2
+)
+UPDATE watchlist
+SET position = CASE
+    WHEN watchlist.title_id = $2 THEN moved.new_position
+    WHEN moved.new_position > moved.old_position THEN watchlist.position - 1
+    ELSE watchlist.position + 1
+END
+FROM moved
+WHERE watchlist.profile_id = $1
+    AND watchlist.position BETWEEN LEAST(moved.old_position, moved.new_position)
+        AND GREATEST(moved.old_position, moved.new_position);
\ No newline at end of file
@@ -0,0 +1,14 @@
+UPDATE users
+SET
+    pin_failed_attempts = CASE
+        WHEN pin_locked_until IS NULL THEN pin_failed_attempts + 1
+        ELSE 1
+    END,
+    pin_locked_until = CASE
+        WHEN pin_locked_until IS NULL AND pin_failed_attempts + 1 >= $2
+            THEN now() + make_interval(secs => $3)
+    END
+WHERE id = $1
+    AND parental_pin_hash IS NOT NULL
+    AND (pin_locked_until IS NULL OR pin_locked_until <= now())
+RETURNING parental_pin_hash;
\ No newline at end of file
@@ -0,0 +1,12 @@
+UPDATE users
+SET
+    user_code_attempts = CASE
+        WHEN user_code_locked_until IS NULL THEN user_code_attempts + 1
+        ELSE 1
+    END,
+    user_code_locked_until = CASE
+        WHEN user_code_locked_until IS NULL AND user_code_attempts + 1 >= $2
+            THEN now() + make_interval(secs => $3)
+    END
+WHERE id = $1
+    AND (user_code_locked_until IS NULL OR user_code_locked_until <= now());
\ No newline at end of file
@@ -0,0 +1,3 @@
+UPDATE users
+SET user_code_attempts = 0, user_code_locked_until = NULL
+WHERE id = $1;
\ No newline at end of file
@@ -0,0 +1,3 @@
+UPDATE refresh_tokens
+SET revoked_at = now()
+WHERE id = $1;
\ No newline at end of file
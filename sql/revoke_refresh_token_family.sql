@@ -0,0 +1,4 @@
+UPDATE refresh_tokens
+SET revoked_at = now()
+WHERE family = (SELECT family FROM refresh_tokens WHERE token_hash = $1)
+    AND revoked_at IS NULL;
\ No newline at end of file
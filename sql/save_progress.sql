@@ -0,0 +1,30 @@
+-- titles above the age rating limit of the profile are not saved
+WITH target AS (
+    SELECT seasons.title_id, episodes.id AS episode_id
+    FROM episodes
+    JOIN seasons ON seasons.id = episodes.season_id
+    WHERE episodes.id = $4
+    UNION ALL
+    SELECT id, NULL
+    FROM titles
+    WHERE $4::BIGINT IS NULL AND id = $3
+)
+INSERT INTO watch_progress
+    (profile_id, title_id, episode_id, position_secs, duration_secs, completed)
+SELECT
+    profiles.id,
+    target.title_id,
+    target.episode_id,
+    LEAST($5::INTEGER, $6::INTEGER),
+    $6::INTEGER,
+    $5::INTEGER >= $6::INTEGER * $7::FLOAT8
+FROM profiles, target
+JOIN titles ON titles.id = target.title_id
+WHERE profiles.id = $1 AND profiles.user_id = $2
+    AND titles.age_rating <= profiles.max_age_rating
+ON CONFLICT (profile_id, title_id, (COALESCE(episode_id, 0))) DO UPDATE
+SET
+    position_secs = EXCLUDED.position_secs,
+    duration_secs = EXCLUDED.duration_secs,
+    completed = EXCLUDED.completed,
+    updated_at = now();
\ No newline at end of file
@@ -1,13 +0,0 @@
-DROP SCHEMA IF EXISTS testing
-CASCADE;
-CREATE SCHEMA testing;
-
-CREATE TABLE testing.users
-(
-    id BIGSERIAL PRIMARY KEY,
-    email VARCHAR(200) NOT NULL,
-    first_name VARCHAR(200) NOT NULL,
-    last_name VARCHAR(200) NOT NULL,
-    username VARCHAR(50) UNIQUE NOT NULL,
-    UNIQUE (username)
-);
\ No newline at end of file
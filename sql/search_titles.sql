@@ -0,0 +1,9 @@
+SELECT $table_fields,
+    ts_headline('simple', titles.name, query, 'HighlightAll=true') AS highlight,
+    ts_headline('simple', titles.synopsis, query, 'MaxFragments=1, MinWords=5, MaxWords=20') AS snippet
+FROM titles, to_tsquery('simple', $1) AS query
+WHERE titles.search_vector @@ query
+    AND ($2::SMALLINT IS NULL OR titles.age_rating <= $2)
+ORDER BY ts_rank(titles.search_vector, query) DESC, titles.name, titles.id
+LIMIT $3
+OFFSET $4;
\ No newline at end of file
@@ -0,0 +1,6 @@
+SELECT EXISTS (
+    SELECT 1
+    FROM titles
+    WHERE search_vector @@ to_tsquery('simple', $1)
+        AND ($2::SMALLINT IS NULL OR age_rating <= $2)
+) AS found;
\ No newline at end of file
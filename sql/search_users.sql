@@ -0,0 +1,12 @@
+SELECT $table_fields,
+    ts_headline(
+        'simple',
+        users.first_name || ' ' || users.last_name || ' (' || users.username || ')',
+        query,
+        'HighlightAll=true'
+    ) AS highlight
+FROM users, to_tsquery('simple', $1) AS query
+WHERE users.search_vector @@ query
+ORDER BY ts_rank(users.search_vector, query) DESC, users.username, users.id
+LIMIT $2
+OFFSET $3;
\ No newline at end of file
@@ -0,0 +1,5 @@
+SELECT EXISTS (
+    SELECT 1
+    FROM users
+    WHERE search_vector @@ to_tsquery('simple', $1)
+) AS found;
\ No newline at end of file
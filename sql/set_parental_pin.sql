@@ -0,0 +1,3 @@
+UPDATE users
+SET parental_pin_hash = $2, pin_failed_attempts = 0, pin_locked_until = NULL
+WHERE id = $1;
\ No newline at end of file
@@ -0,0 +1,3 @@
+UPDATE devices
+SET last_seen_at = now()
+WHERE id = $1 AND last_seen_at < now() - interval '1 minute';
\ No newline at end of file
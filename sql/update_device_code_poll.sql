@@ -0,0 +1,3 @@
+UPDATE device_codes
+SET last_polled_at = now(), interval_secs = interval_secs + $2, status = $3
+WHERE id = $1;
\ No newline at end of file
@@ -0,0 +1,9 @@
+UPDATE profiles
+SET
+    name = COALESCE($3, name),
+    avatar = COALESCE($4, avatar),
+    is_kids = COALESCE($5, is_kids),
+    language = COALESCE($6, language),
+    max_age_rating = COALESCE($7, max_age_rating)
+WHERE id = $1 AND user_id = $2
+RETURNING $table_fields;
\ No newline at end of file
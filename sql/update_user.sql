@@ -0,0 +1,9 @@
+UPDATE users
+SET
+    email = COALESCE($2, email),
+    first_name = COALESCE($3, first_name),
+    last_name = COALESCE($4, last_name),
+    username = COALESCE($5, username),
+    password_hash = COALESCE($6, password_hash)
+WHERE id = $1
+RETURNING $table_fields;
\ No newline at end of file
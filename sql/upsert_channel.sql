@@ -0,0 +1,5 @@
+INSERT INTO channels (xmltv_id, name, icon)
+VALUES ($1, $2, $3)
+ON CONFLICT (xmltv_id) DO UPDATE
+SET name = EXCLUDED.name, icon = COALESCE(EXCLUDED.icon, channels.icon)
+RETURNING id;
\ No newline at end of file
@@ -0,0 +1,38 @@
+use rand::{rngs::OsRng, Rng};
+
+use super::tokens::{hash_refresh_token, random_hex};
+
+/// Consonants only, so codes can't spell words and survive being read aloud,
+/// as recommended by RFC 8628.
+const USER_CODE_ALPHABET: &[u8] = b"BCDFGHJKLMNPQRSTVWXZ";
+const USER_CODE_LEN: usize = 8;
+
+/// Secret polled by the TV, stored hashed like refresh tokens.
+pub fn generate_device_code() -> String {
+    random_hex(32)
+}
+
+pub fn hash_device_code(code: &str) -> String {
+    hash_refresh_token(code)
+}
+
+pub fn generate_user_code() -> String {
+    (0..USER_CODE_LEN)
+        .map(|_| USER_CODE_ALPHABET[OsRng.gen_range(0..USER_CODE_ALPHABET.len())] as char)
+        .collect()
+}
+
+/// Shows `BCDFGHJK` as `BCDF-GHJK`.
+pub fn format_user_code(code: &str) -> String {
+    let (first, second) = code.split_at(code.len() / 2);
+    format!("{}-{}", first, second)
+}
+
+/// Accepts codes typed in lower case or with separators, e.g. `bcdf ghjk`.
+pub fn normalize_user_code(input: &str) -> String {
+    input
+        .chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_uppercase())
+        .collect()
+}
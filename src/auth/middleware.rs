@@ -0,0 +1,106 @@
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+};
+
+use actix_web::{
+    dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform},
+    http::header,
+    web, Error, FromRequest, HttpMessage,
+};
+use deadpool_postgres::{Client, Pool};
+
+use super::tokens::authenticate;
+use crate::{db, errors::MyError};
+
+/// Rejects requests without a valid bearer token with 401, and makes the
+/// `AuthenticatedUser` available to the wrapped handlers. Tokens issued to a
+/// device are rejected once the device is removed.
+pub struct RequireAuth;
+
+impl<S, B> Transform<S, ServiceRequest> for RequireAuth
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = AuthMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AuthMiddleware {
+            service: Rc::new(service),
+            required: true,
+        }))
+    }
+}
+
+/// Like `RequireAuth` for requests with an `Authorization` header, lets anonymous
+/// requests through so handlers can take an `Option<AuthenticatedUser>`.
+pub struct OptionalAuth;
+
+impl<S, B> Transform<S, ServiceRequest> for OptionalAuth
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = AuthMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AuthMiddleware {
+            service: Rc::new(service),
+            required: false,
+        }))
+    }
+}
+
+pub struct AuthMiddleware<S> {
+    service: Rc<S>,
+    required: bool,
+}
+
+impl<S, B> Service<ServiceRequest> for AuthMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let service = Rc::clone(&self.service);
+        let anonymous = !req.headers().contains_key(header::AUTHORIZATION);
+        if anonymous && !self.required {
+            return Box::pin(service.call(req));
+        }
+
+        Box::pin(async move {
+            let user = authenticate(req.request())?;
+
+            if let Some(device_id) = user.device_id {
+                let db_pool = web::Data::<Pool>::extract(req.request()).await?;
+                let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+                if !db::device::touch_device(&client, device_id, user.user_id).await? {
+                    return Err(MyError::Unauthorized.into());
+                }
+            }
+
+            req.extensions_mut().insert(user);
+            service.call(req).await
+        })
+    }
+}
@@ -0,0 +1,21 @@
+//! Credentials and tokens: Argon2id password hashes, JWT access tokens and
+//! opaque refresh tokens, device codes, role based permission checks and parental PINs.
+
+mod device;
+mod middleware;
+mod parental;
+mod password;
+mod permissions;
+mod tokens;
+
+pub use device::{
+    format_user_code, generate_device_code, generate_user_code, hash_device_code,
+    normalize_user_code,
+};
+pub use middleware::{OptionalAuth, RequireAuth};
+pub use parental::{require_parental_pin, verify_parental_pin};
+pub use password::{hash_password, require_current_password, verify_password};
+pub use permissions::Permission;
+pub use tokens::{
+    generate_refresh_token, generate_token_family, hash_refresh_token, AuthenticatedUser, JwtKeys,
+};
@@ -0,0 +1,49 @@
+//! Parental PIN checks, the PIN is hashed like a password and locked after
+//! repeated failures.
+
+use actix_web::{web, Error};
+use deadpool_postgres::Client;
+
+use super::verify_password;
+use crate::{db, errors::MyError};
+
+const MAX_PIN_ATTEMPTS: i32 = 5;
+const PIN_LOCK_SECS: f64 = 15.0 * 60.0;
+
+/// Checks `pin` against the user's PIN, a user without a PIN is `NotFound`.
+pub async fn verify_parental_pin(client: &Client, user_id: i64, pin: &str) -> Result<(), Error> {
+    if !db::parental::has_parental_pin(client, user_id).await? {
+        return Err(MyError::NotFound.into());
+    }
+
+    check_pin(client, user_id, pin).await
+}
+
+/// Like `verify_parental_pin`, but passes when the user has no PIN set.
+pub async fn require_parental_pin(
+    client: &Client,
+    user_id: i64,
+    pin: Option<&str>,
+) -> Result<(), Error> {
+    if !db::parental::has_parental_pin(client, user_id).await? {
+        return Ok(());
+    }
+
+    let pin = pin.ok_or(MyError::PinRequired)?;
+    check_pin(client, user_id, pin).await
+}
+
+async fn check_pin(client: &Client, user_id: i64, pin: &str) -> Result<(), Error> {
+    let hash = db::parental::reserve_pin_attempt(client, user_id, MAX_PIN_ATTEMPTS, PIN_LOCK_SECS)
+        .await?
+        .ok_or(MyError::PinLocked)?;
+
+    let pin = pin.to_string();
+    match web::block(move || verify_password(&pin, Some(&hash))).await? {
+        true => {
+            db::parental::reset_pin_attempts(client, user_id).await?;
+            Ok(())
+        }
+        false => Err(MyError::InvalidPin.into()),
+    }
+}
@@ -0,0 +1,54 @@
+//! Password hashing with Argon2id.
+
+use actix_web::{web, Error};
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, PasswordHasher, PasswordVerifier, SaltString},
+    Argon2,
+};
+use deadpool_postgres::Client;
+use lazy_static::lazy_static;
+
+use crate::{db, errors::MyError};
+
+lazy_static! {
+    /// Verified against when the user is unknown, so a failed login takes as long
+    /// whether or not the username exists.
+    static ref DUMMY_HASH: String = hash_password("not a real password").unwrap();
+}
+
+pub fn hash_password(password: &str) -> Result<String, MyError> {
+    let salt = SaltString::generate(&mut OsRng);
+    Ok(Argon2::default()
+        .hash_password(password.as_bytes(), &salt)?
+        .to_string())
+}
+
+/// Returns whether `password` matches `hash`, a missing hash never matches.
+pub fn verify_password(password: &str, hash: Option<&str>) -> bool {
+    let parsed = match PasswordHash::new(hash.unwrap_or(&DUMMY_HASH)) {
+        Ok(parsed) => parsed,
+        Err(_) => return false,
+    };
+    let matches = Argon2::default()
+        .verify_password(password.as_bytes(), &parsed)
+        .is_ok();
+
+    matches && hash.is_some()
+}
+
+/// Confirms a user's current password before they change it themselves, so an
+/// access token alone is not enough to take over the account.
+pub async fn require_current_password(
+    client: &Client,
+    user_id: i64,
+    password: Option<&str>,
+) -> Result<(), Error> {
+    let password = password.ok_or(MyError::PasswordRequired)?.to_string();
+    let hash = db::auth::get_password_hash(client, user_id).await?;
+
+    let valid = web::block(move || verify_password(&password, hash.as_deref())).await?;
+    match valid {
+        true => Ok(()),
+        false => Err(MyError::InvalidPassword.into()),
+    }
+}
@@ -0,0 +1,89 @@
+use deadpool_postgres::Client;
+
+use super::AuthenticatedUser;
+use crate::{db, errors::MyError, models::profiles::Profile};
+
+/// Permissions granted through roles, see the `create_roles` migration.
+#[derive(Clone, Copy, Debug)]
+pub enum Permission {
+    ReadUsers,
+    WriteUsers,
+    ManageRoles,
+}
+
+impl Permission {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Permission::ReadUsers => "users:read",
+            Permission::WriteUsers => "users:write",
+            Permission::ManageRoles => "roles:manage",
+        }
+    }
+}
+
+impl AuthenticatedUser {
+    /// Whether one of the user's roles grants `permission`.
+    pub async fn has_permission(
+        self,
+        client: &Client,
+        permission: Permission,
+    ) -> Result<bool, MyError> {
+        db::roles::has_permission(client, self.user_id, permission.as_str()).await
+    }
+
+    /// Fails with 403 unless one of the user's roles grants `permission`.
+    pub async fn require_permission(
+        self,
+        client: &Client,
+        permission: Permission,
+    ) -> Result<(), MyError> {
+        if self.has_permission(client, permission).await? {
+            Ok(())
+        } else {
+            Err(MyError::Forbidden)
+        }
+    }
+
+    /// Users always have access to their own resources, others need `permission`.
+    pub async fn require_self_or_permission(
+        self,
+        client: &Client,
+        user_id: i64,
+        permission: Permission,
+    ) -> Result<(), MyError> {
+        if self.user_id == user_id {
+            return Ok(());
+        }
+        self.require_permission(client, permission).await
+    }
+
+    /// Tokens scoped to a profile may only access that profile.
+    pub fn require_profile_scope(self, profile_id: i64) -> Result<(), MyError> {
+        match self.profile_id {
+            Some(selected) if selected != profile_id => Err(MyError::Forbidden),
+            _ => Ok(()),
+        }
+    }
+
+    /// Loads one of the user's profiles, within the scope of the token.
+    pub async fn require_profile(
+        self,
+        client: &Client,
+        profile_id: i64,
+    ) -> Result<Profile, MyError> {
+        self.require_profile_scope(profile_id)?;
+        db::profiles::get_profile(client, profile_id, self.user_id).await
+    }
+
+    /// Age rating limit of the selected profile, `None` for tokens without one.
+    pub async fn age_rating_limit(self, client: &Client) -> Result<Option<i16>, MyError> {
+        match self.profile_id {
+            Some(profile_id) => Ok(Some(
+                self.require_profile(client, profile_id)
+                    .await?
+                    .max_age_rating,
+            )),
+            None => Ok(None),
+        }
+    }
+}
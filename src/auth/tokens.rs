@@ -0,0 +1,211 @@
+use std::{
+    fs,
+    future::{ready, Ready},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use actix_web::{dev::Payload, http::header, web, FromRequest, HttpMessage, HttpRequest};
+use jsonwebtoken::{
+    decode, encode, errors::Error as JwtError, Algorithm, DecodingKey, EncodingKey, Header,
+    Validation,
+};
+use rand::{rngs::OsRng, RngCore};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    config::{JwtAlgorithm, JwtConfig},
+    errors::MyError,
+};
+
+/// HS256 secrets shorter than the hash output make tokens cheaper to forge.
+const MIN_SECRET_LEN: usize = 32;
+
+/// The `.env.example` placeholder and the secret once committed to `.env`.
+const PUBLIC_SECRETS: [&str; 2] = [
+    "replace-with-at-least-32-random-bytes",
+    "change-me-in-production-0123456789abcdef",
+];
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Claims {
+    /// User id, as a string per RFC 7519.
+    pub sub: String,
+    pub iat: u64,
+    pub exp: u64,
+    /// Registered device the token was issued to, see `RequireAuth`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub did: Option<i64>,
+    /// Selected viewer profile, the token can only access that profile.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pid: Option<i64>,
+}
+
+pub struct JwtKeys {
+    algorithm: Algorithm,
+    encoding: EncodingKey,
+    decoding: DecodingKey,
+    pub access_ttl_secs: u64,
+    pub refresh_ttl_secs: u64,
+}
+
+impl JwtKeys {
+    pub fn from_config(config: &JwtConfig) -> Result<Self, String> {
+        let (algorithm, encoding, decoding) = match config.algorithm {
+            JwtAlgorithm::HS256 => {
+                let secret = config
+                    .secret
+                    .as_deref()
+                    .filter(|secret| !secret.is_empty())
+                    .ok_or("JWT.SECRET is required for HS256")?;
+                if secret.len() < MIN_SECRET_LEN {
+                    return Err(format!(
+                        "JWT.SECRET must be at least {} bytes long",
+                        MIN_SECRET_LEN
+                    ));
+                }
+                if PUBLIC_SECRETS.contains(&secret) {
+                    return Err("JWT.SECRET is a published example, generate a new one".into());
+                }
+                (
+                    Algorithm::HS256,
+                    EncodingKey::from_secret(secret.as_bytes()),
+                    DecodingKey::from_secret(secret.as_bytes()),
+                )
+            }
+            JwtAlgorithm::RS256 => {
+                let (private_key, public_key) = read_key_pair(config)?;
+                (
+                    Algorithm::RS256,
+                    EncodingKey::from_rsa_pem(&private_key).map_err(key_error)?,
+                    DecodingKey::from_rsa_pem(&public_key).map_err(key_error)?,
+                )
+            }
+            JwtAlgorithm::EdDSA => {
+                let (private_key, public_key) = read_key_pair(config)?;
+                (
+                    Algorithm::EdDSA,
+                    EncodingKey::from_ed_pem(&private_key).map_err(key_error)?,
+                    DecodingKey::from_ed_pem(&public_key).map_err(key_error)?,
+                )
+            }
+        };
+
+        Ok(JwtKeys {
+            algorithm,
+            encoding,
+            decoding,
+            access_ttl_secs: config.access_ttl_secs,
+            refresh_ttl_secs: config.refresh_ttl_secs,
+        })
+    }
+
+    pub fn issue_access_token(&self, subject: AuthenticatedUser) -> Result<String, MyError> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|since_epoch| since_epoch.as_secs())
+            .unwrap_or_default();
+        let claims = Claims {
+            sub: subject.user_id.to_string(),
+            iat: now,
+            exp: now + self.access_ttl_secs,
+            did: subject.device_id,
+            pid: subject.profile_id,
+        };
+
+        Ok(encode(
+            &Header::new(self.algorithm),
+            &claims,
+            &self.encoding,
+        )?)
+    }
+
+    pub fn verify_access_token(&self, token: &str) -> Result<Claims, MyError> {
+        decode::<Claims>(token, &self.decoding, &Validation::new(self.algorithm))
+            .map(|data| data.claims)
+            .map_err(|_| MyError::Unauthorized)
+    }
+}
+
+fn read_key_pair(config: &JwtConfig) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let read = |path: &Option<String>, name: &str| {
+        let path = path
+            .as_deref()
+            .ok_or_else(|| format!("JWT.{} is required for {:?}", name, config.algorithm))?;
+        fs::read(path).map_err(|err| format!("cannot read {}: {}", path, err))
+    };
+
+    Ok((
+        read(&config.private_key_file, "PRIVATE_KEY_FILE")?,
+        read(&config.public_key_file, "PUBLIC_KEY_FILE")?,
+    ))
+}
+
+fn key_error(err: JwtError) -> String {
+    format!("invalid JWT key: {}", err)
+}
+
+/// Random opaque token handed to the client, only its hash is stored.
+pub fn generate_refresh_token() -> String {
+    random_hex(32)
+}
+
+/// Groups a refresh token with the ones it is rotated into, so reuse of any of
+/// them can revoke the whole chain.
+pub fn generate_token_family() -> String {
+    random_hex(16)
+}
+
+pub fn hash_refresh_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
+}
+
+pub(super) fn random_hex(len: usize) -> String {
+    let mut bytes = vec![0u8; len];
+    OsRng.fill_bytes(&mut bytes);
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Owner of the bearer token of the request, only available on routes wrapped
+/// in `RequireAuth`, which rejects missing or invalid tokens with 401.
+#[derive(Clone, Copy)]
+pub struct AuthenticatedUser {
+    pub user_id: i64,
+    pub device_id: Option<i64>,
+    pub profile_id: Option<i64>,
+}
+
+impl FromRequest for AuthenticatedUser {
+    type Error = MyError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        ready(
+            req.extensions()
+                .get::<AuthenticatedUser>()
+                .copied()
+                .ok_or(MyError::Unauthorized),
+        )
+    }
+}
+
+pub(super) fn authenticate(req: &HttpRequest) -> Result<AuthenticatedUser, MyError> {
+    let keys = req
+        .app_data::<web::Data<JwtKeys>>()
+        .ok_or(MyError::Unauthorized)?;
+    let token = req
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .ok_or(MyError::Unauthorized)?;
+
+    let claims = keys.verify_access_token(token)?;
+    let user_id = claims.sub.parse().map_err(|_| MyError::Unauthorized)?;
+
+    Ok(AuthenticatedUser {
+        user_id,
+        device_id: claims.did,
+        profile_id: claims.pid,
+    })
+}
@@ -0,0 +1,173 @@
+use deadpool_postgres::SslMode;
+use serde::Deserialize;
+#[derive(Debug, Default, Deserialize)]
+pub struct ExampleConfig {
+    pub server_addr: String,
+    pub pg: deadpool_postgres::Config,
+    #[serde(default)]
+    pub pg_tls: TlsConfig,
+    /// Schema holding all tables, lets staging and prod share one cluster.
+    #[serde(default = "default_pg_schema")]
+    pub pg_schema: String,
+    /// Apply pending migrations before the server starts accepting requests.
+    #[serde(default)]
+    pub run_migrations: bool,
+    #[serde(default)]
+    pub jwt: JwtConfig,
+    #[serde(default)]
+    pub device: DeviceConfig,
+    #[serde(default)]
+    pub log: LogConfig,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TlsConfig {
+    #[serde(default)]
+    pub mode: TlsMode,
+    /// PEM bundle used to verify the server in `verify-full` mode, defaults to the webpki roots.
+    pub ca_file: Option<String>,
+    /// PEM client certificate and key, for servers that require certificate authentication.
+    pub cert_file: Option<String>,
+    pub key_file: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TlsMode {
+    #[default]
+    Disable,
+    Prefer,
+    Require,
+    VerifyFull,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct JwtConfig {
+    pub algorithm: JwtAlgorithm,
+    /// Shared secret, only used with HS256.
+    pub secret: Option<String>,
+    /// PEM encoded key pair, used with RS256 and EdDSA.
+    pub private_key_file: Option<String>,
+    pub public_key_file: Option<String>,
+    pub access_ttl_secs: u64,
+    pub refresh_ttl_secs: u64,
+}
+
+impl Default for JwtConfig {
+    fn default() -> Self {
+        JwtConfig {
+            algorithm: JwtAlgorithm::default(),
+            secret: None,
+            private_key_file: None,
+            public_key_file: None,
+            access_ttl_secs: 15 * 60,
+            refresh_ttl_secs: 30 * 24 * 60 * 60,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+pub enum JwtAlgorithm {
+    #[default]
+    HS256,
+    RS256,
+    EdDSA,
+}
+
+/// RFC 8628 device authorization grant, used to sign in on TVs.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+pub struct DeviceConfig {
+    /// Page of the web app where the user enters the code shown on the TV,
+    /// required since this server does not serve one.
+    pub verification_uri: String,
+    pub code_ttl_secs: u64,
+    /// Minimum number of seconds between two polls of `/device/token`.
+    pub interval_secs: u64,
+}
+
+impl Default for DeviceConfig {
+    fn default() -> Self {
+        DeviceConfig {
+            verification_uri: String::new(),
+            code_ttl_secs: 10 * 60,
+            interval_secs: 5,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(default)]
+pub struct LogConfig {
+    /// `EnvFilter` directives, e.g. `info` or `info,vidaa_server::db=debug`.
+    pub level: String,
+    pub format: LogFormat,
+}
+
+impl Default for LogConfig {
+    fn default() -> Self {
+        LogConfig {
+            level: "info".into(),
+            format: LogFormat::default(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+    #[default]
+    Pretty,
+    Json,
+}
+
+impl DeviceConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        let absolute = ["https://", "http://"]
+            .iter()
+            .any(|scheme| self.verification_uri.starts_with(scheme));
+        if !absolute {
+            return Err(
+                "DEVICE.VERIFICATION_URI must be the absolute URL of the code entry page".into(),
+            );
+        }
+        Ok(())
+    }
+}
+
+fn default_pg_schema() -> String {
+    "testing".into()
+}
+
+impl ExampleConfig {
+    /// Connection settings with `search_path` pointing at `pg_schema`, so SQL files
+    /// can use unqualified table names.
+    pub fn pg_config(&self) -> Result<deadpool_postgres::Config, String> {
+        let valid = self
+            .pg_schema
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+            && self
+                .pg_schema
+                .starts_with(|c: char| c.is_ascii_lowercase() || c == '_');
+        if !valid {
+            return Err(format!("invalid PG_SCHEMA '{}'", self.pg_schema));
+        }
+
+        let mut pg = self.pg.clone();
+        let search_path = format!("-c search_path={}", self.pg_schema);
+        pg.options = Some(match pg.options {
+            Some(options) => format!("{} {}", options, search_path),
+            None => search_path,
+        });
+
+        pg.ssl_mode = Some(match self.pg_tls.mode {
+            TlsMode::Disable => SslMode::Disable,
+            TlsMode::Prefer => SslMode::Prefer,
+            TlsMode::Require | TlsMode::VerifyFull => SslMode::Require,
+        });
+
+        Ok(pg)
+    }
+}
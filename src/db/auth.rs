@@ -0,0 +1,186 @@
+use deadpool_postgres::Client;
+use lazy_static::lazy_static;
+use tokio_pg_mapper::FromTokioPostgresRow;
+use tracing::{field::Empty, instrument};
+
+use super::{record_rows, with_table_fields};
+use crate::{auth::AuthenticatedUser, errors::MyError, models::User};
+
+lazy_static! {
+    static ref GET_USER_CREDENTIALS: String =
+        with_table_fields(include_str!("../../sql/get_user_credentials.sql"));
+}
+const ADD_REFRESH_TOKEN: &str = include_str!("../../sql/add_refresh_token.sql");
+const GET_REFRESH_TOKEN: &str = include_str!("../../sql/get_refresh_token.sql");
+const REVOKE_REFRESH_TOKEN: &str = include_str!("../../sql/revoke_refresh_token.sql");
+const REVOKE_REFRESH_TOKEN_FAMILY: &str = include_str!("../../sql/revoke_refresh_token_family.sql");
+const REVOKE_USER_REFRESH_TOKENS: &str = include_str!("../../sql/revoke_user_refresh_tokens.sql");
+const GET_PASSWORD_HASH: &str = include_str!("../../sql/get_password_hash.sql");
+
+pub(super) async fn prepare_statements(client: &Client) -> Result<(), MyError> {
+    let statements = [
+        GET_USER_CREDENTIALS.as_str(),
+        ADD_REFRESH_TOKEN,
+        GET_REFRESH_TOKEN,
+        REVOKE_REFRESH_TOKEN,
+        REVOKE_REFRESH_TOKEN_FAMILY,
+        REVOKE_USER_REFRESH_TOKENS,
+        GET_PASSWORD_HASH,
+    ];
+
+    for stmt in statements {
+        client.prepare_cached(stmt).await?;
+    }
+
+    Ok(())
+}
+
+/// `None` for accounts created before passwords were introduced, an unknown
+/// user is `NotFound`.
+#[instrument(level = "debug", skip_all, fields(stmt = "get_password_hash", rows = Empty))]
+pub async fn get_password_hash(client: &Client, user_id: i64) -> Result<Option<String>, MyError> {
+    let stmt = client.prepare_cached(GET_PASSWORD_HASH).await?;
+
+    let row = record_rows(client.query_opt(&stmt, &[&user_id]).await?).ok_or(MyError::NotFound)?;
+
+    Ok(row.try_get("password_hash")?)
+}
+
+/// Looks up a user by username together with their password hash, which is
+/// `None` for accounts created before passwords were introduced.
+#[instrument(level = "debug", skip_all, fields(stmt = "get_user_credentials", rows = Empty))]
+pub async fn get_credentials(
+    client: &Client,
+    username: &str,
+) -> Result<Option<(User, Option<String>)>, MyError> {
+    let stmt = client.prepare_cached(&GET_USER_CREDENTIALS).await?;
+
+    match record_rows(client.query_opt(&stmt, &[&username]).await?) {
+        Some(row) => Ok(Some((
+            User::from_row_ref(&row)?,
+            row.try_get("password_hash")?,
+        ))),
+        None => Ok(None),
+    }
+}
+
+#[instrument(level = "debug", skip_all, fields(stmt = "add_refresh_token", rows = Empty))]
+pub async fn add_refresh_token(
+    client: &Client,
+    subject: AuthenticatedUser,
+    family: &str,
+    token_hash: &str,
+    ttl_secs: u64,
+) -> Result<(), MyError> {
+    let stmt = client.prepare_cached(ADD_REFRESH_TOKEN).await?;
+
+    record_rows(
+        client
+            .execute(
+                &stmt,
+                &[
+                    &subject.user_id,
+                    &family,
+                    &token_hash,
+                    &(ttl_secs as f64),
+                    &subject.device_id,
+                    &subject.profile_id,
+                ],
+            )
+            .await?,
+    );
+
+    Ok(())
+}
+
+/// Revokes the refresh token identified by `token_hash` and stores `new_hash` in
+/// its place, returning who the token was issued to. Presenting an already revoked token
+/// is treated as theft and revokes every token of its family. Returns `None`
+/// for unknown, expired or reused tokens.
+#[instrument(level = "debug", skip_all, fields(stmt = "get_refresh_token", rows = Empty))]
+pub async fn rotate_refresh_token(
+    client: &mut Client,
+    token_hash: &str,
+    new_hash: &str,
+    ttl_secs: u64,
+) -> Result<Option<AuthenticatedUser>, MyError> {
+    let transaction = client.transaction().await?;
+
+    let row = match record_rows(
+        transaction
+            .query_opt(
+                &transaction.prepare_cached(GET_REFRESH_TOKEN).await?,
+                &[&token_hash],
+            )
+            .await?,
+    ) {
+        Some(row) => row,
+        None => return Ok(None),
+    };
+    let id: i64 = row.try_get("id")?;
+    let subject = AuthenticatedUser {
+        user_id: row.try_get("user_id")?,
+        device_id: row.try_get("device_id")?,
+        profile_id: row.try_get("profile_id")?,
+    };
+    let family: String = row.try_get("family")?;
+
+    if row.try_get("revoked")? {
+        transaction
+            .execute(
+                &transaction
+                    .prepare_cached(REVOKE_REFRESH_TOKEN_FAMILY)
+                    .await?,
+                &[&token_hash],
+            )
+            .await?;
+        transaction.commit().await?;
+        return Ok(None);
+    }
+    if row.try_get("expired")? {
+        return Ok(None);
+    }
+
+    transaction
+        .execute(
+            &transaction.prepare_cached(REVOKE_REFRESH_TOKEN).await?,
+            &[&id],
+        )
+        .await?;
+    transaction
+        .execute(
+            &transaction.prepare_cached(ADD_REFRESH_TOKEN).await?,
+            &[
+                &subject.user_id,
+                &family,
+                &new_hash,
+                &(ttl_secs as f64),
+                &subject.device_id,
+                &subject.profile_id,
+            ],
+        )
+        .await?;
+    transaction.commit().await?;
+
+    Ok(Some(subject))
+}
+
+/// Revokes every token rotated from the same login as `token_hash`.
+#[instrument(level = "debug", skip_all, fields(stmt = "revoke_refresh_token_family", rows = Empty))]
+pub async fn revoke_refresh_token_family(client: &Client, token_hash: &str) -> Result<(), MyError> {
+    let stmt = client.prepare_cached(REVOKE_REFRESH_TOKEN_FAMILY).await?;
+
+    record_rows(client.execute(&stmt, &[&token_hash]).await?);
+
+    Ok(())
+}
+
+/// Revokes every refresh token of the user, signing out all of their sessions.
+#[instrument(level = "debug", skip_all, fields(stmt = "revoke_user_refresh_tokens", rows = Empty))]
+pub async fn revoke_user_refresh_tokens(client: &Client, user_id: i64) -> Result<(), MyError> {
+    let stmt = client.prepare_cached(REVOKE_USER_REFRESH_TOKENS).await?;
+
+    record_rows(client.execute(&stmt, &[&user_id]).await?);
+
+    Ok(())
+}
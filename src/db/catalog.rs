@@ -0,0 +1,236 @@
+use deadpool_postgres::Client;
+use lazy_static::lazy_static;
+use tokio_pg_mapper::FromTokioPostgresRow;
+use tokio_postgres::types::ToSql;
+use tracing::{field::Empty, instrument};
+
+use super::{page_bounds, prefix_query, record_rows, search_terms, with_fields};
+use crate::{
+    errors::MyError,
+    models::catalog::{
+        Asset, Episode, EpisodeDetail, SearchHit, SearchQuery, SearchResults, Season, SeasonDetail,
+        Title, TitleDetail, TitlePage, TitleQuery,
+    },
+};
+
+lazy_static! {
+    static ref GET_TITLES: String = with_fields::<Title>(include_str!("../../sql/get_titles.sql"));
+    static ref GET_TITLE_BY_ID: String =
+        with_fields::<Title>(include_str!("../../sql/get_title_by_id.sql"));
+    static ref GET_TITLE_SEASONS: String =
+        with_fields::<Season>(include_str!("../../sql/get_title_seasons.sql"));
+    static ref GET_SEASON_BY_ID: String =
+        with_fields::<Season>(include_str!("../../sql/get_season_by_id.sql"));
+    static ref GET_SEASON_EPISODES: String =
+        with_fields::<Episode>(include_str!("../../sql/get_season_episodes.sql"));
+    static ref GET_EPISODE_BY_ID: String =
+        with_fields::<Episode>(include_str!("../../sql/get_episode_by_id.sql"));
+    static ref GET_TITLE_ASSETS: String =
+        with_fields::<Asset>(include_str!("../../sql/get_title_assets.sql"));
+    static ref GET_EPISODE_ASSETS: String =
+        with_fields::<Asset>(include_str!("../../sql/get_episode_assets.sql"));
+    static ref SEARCH_TITLES: String =
+        with_fields::<Title>(include_str!("../../sql/search_titles.sql"));
+    static ref FUZZY_SEARCH_TITLES: String =
+        with_fields::<Title>(include_str!("../../sql/fuzzy_search_titles.sql"));
+}
+const COUNT_TITLES: &str = include_str!("../../sql/count_titles.sql");
+const SEARCH_TITLES_EXISTS: &str = include_str!("../../sql/search_titles_exists.sql");
+
+pub(super) async fn prepare_statements(client: &Client) -> Result<(), MyError> {
+    let statements = [
+        GET_TITLES.as_str(),
+        COUNT_TITLES,
+        GET_TITLE_BY_ID.as_str(),
+        GET_TITLE_SEASONS.as_str(),
+        GET_SEASON_BY_ID.as_str(),
+        GET_SEASON_EPISODES.as_str(),
+        GET_EPISODE_BY_ID.as_str(),
+        GET_TITLE_ASSETS.as_str(),
+        GET_EPISODE_ASSETS.as_str(),
+        SEARCH_TITLES.as_str(),
+        SEARCH_TITLES_EXISTS,
+        FUZZY_SEARCH_TITLES.as_str(),
+    ];
+
+    for stmt in statements {
+        client.prepare_cached(stmt).await?;
+    }
+
+    Ok(())
+}
+
+#[instrument(level = "debug", skip_all, fields(stmt = "get_titles", rows = Empty))]
+pub async fn get_titles(client: &Client, query: TitleQuery) -> Result<TitlePage, MyError> {
+    let (limit, offset) = page_bounds(query.limit, query.offset)?;
+
+    // genres are stored lower case
+    let genre = query.genre.map(|genre| genre.trim().to_lowercase());
+    let filters: [&(dyn ToSql + Sync); 4] =
+        [&genre, &query.year, &query.max_age_rating, &query.kind];
+
+    let count_stmt = client.prepare_cached(COUNT_TITLES).await?;
+    let total: i64 = client.query_one(&count_stmt, &filters).await?.get(0);
+
+    let stmt = client.prepare_cached(&GET_TITLES).await?;
+    let items = record_rows(
+        client
+            .query(&stmt, &[&filters[..], &[&limit, &offset]].concat())
+            .await?,
+    )
+    .iter()
+    .map(Title::from_row_ref)
+    .collect::<Result<Vec<Title>, _>>()?;
+
+    Ok(TitlePage { items, total })
+}
+
+/// A title with its seasons, for series, and its assets. Titles rated above
+/// `max_age_rating`, and their seasons and episodes, are `NotFound`.
+#[instrument(level = "debug", skip_all, fields(stmt = "get_title_by_id", rows = Empty))]
+pub async fn get_title(
+    client: &Client,
+    title_id: i64,
+    max_age_rating: Option<i16>,
+) -> Result<TitleDetail, MyError> {
+    let stmt = client.prepare_cached(&GET_TITLE_BY_ID).await?;
+    let row = record_rows(
+        client
+            .query_opt(&stmt, &[&title_id, &max_age_rating])
+            .await?,
+    )
+    .ok_or(MyError::NotFound)?;
+    let title = Title::from_row_ref(&row)?;
+
+    let stmt = client.prepare_cached(&GET_TITLE_SEASONS).await?;
+    let seasons = client
+        .query(&stmt, &[&title_id])
+        .await?
+        .iter()
+        .map(Season::from_row_ref)
+        .collect::<Result<Vec<Season>, _>>()?;
+
+    let stmt = client.prepare_cached(&GET_TITLE_ASSETS).await?;
+    let assets = client
+        .query(&stmt, &[&title_id])
+        .await?
+        .iter()
+        .map(Asset::from_row_ref)
+        .collect::<Result<Vec<Asset>, _>>()?;
+
+    Ok(TitleDetail {
+        title,
+        seasons,
+        assets,
+    })
+}
+
+#[instrument(level = "debug", skip_all, fields(stmt = "get_season_by_id", rows = Empty))]
+pub async fn get_season(
+    client: &Client,
+    season_id: i64,
+    max_age_rating: Option<i16>,
+) -> Result<SeasonDetail, MyError> {
+    let stmt = client.prepare_cached(&GET_SEASON_BY_ID).await?;
+    let row = record_rows(
+        client
+            .query_opt(&stmt, &[&season_id, &max_age_rating])
+            .await?,
+    )
+    .ok_or(MyError::NotFound)?;
+    let season = Season::from_row_ref(&row)?;
+
+    let stmt = client.prepare_cached(&GET_SEASON_EPISODES).await?;
+    let episodes = client
+        .query(&stmt, &[&season_id])
+        .await?
+        .iter()
+        .map(Episode::from_row_ref)
+        .collect::<Result<Vec<Episode>, _>>()?;
+
+    Ok(SeasonDetail { season, episodes })
+}
+
+#[instrument(level = "debug", skip_all, fields(stmt = "get_episode_by_id", rows = Empty))]
+pub async fn get_episode(
+    client: &Client,
+    episode_id: i64,
+    max_age_rating: Option<i16>,
+) -> Result<EpisodeDetail, MyError> {
+    let stmt = client.prepare_cached(&GET_EPISODE_BY_ID).await?;
+    let row = record_rows(
+        client
+            .query_opt(&stmt, &[&episode_id, &max_age_rating])
+            .await?,
+    )
+    .ok_or(MyError::NotFound)?;
+    let episode = Episode::from_row_ref(&row)?;
+
+    let stmt = client.prepare_cached(&GET_EPISODE_ASSETS).await?;
+    let assets = client
+        .query(&stmt, &[&episode_id])
+        .await?
+        .iter()
+        .map(Asset::from_row_ref)
+        .collect::<Result<Vec<Asset>, _>>()?;
+
+    Ok(EpisodeDetail { episode, assets })
+}
+
+/// Matches every word of `q` as a prefix of words in the name or synopsis,
+/// ranking name matches first. When nothing matches, falls back to names
+/// similar to `q` so typos still find something.
+#[instrument(level = "debug", skip_all, fields(stmt = "search_titles", rows = Empty))]
+pub async fn search_titles(
+    client: &Client,
+    query: SearchQuery,
+    max_age_rating: Option<i16>,
+) -> Result<SearchResults, MyError> {
+    let (limit, offset) = page_bounds(query.limit, query.offset)?;
+
+    let terms = search_terms(&query.q)?;
+    let prefix_query = prefix_query(&terms);
+
+    let stmt = client.prepare_cached(&SEARCH_TITLES).await?;
+    let rows = record_rows(
+        client
+            .query(&stmt, &[&prefix_query, &max_age_rating, &limit, &offset])
+            .await?,
+    );
+
+    // an empty page past the first one may just be the end of the matches
+    let fuzzy = rows.is_empty()
+        && (offset == 0 || {
+            let stmt = client.prepare_cached(SEARCH_TITLES_EXISTS).await?;
+            let row = client
+                .query_one(&stmt, &[&prefix_query, &max_age_rating])
+                .await?;
+            !row.try_get::<_, bool>("found")?
+        });
+    let rows = match fuzzy {
+        true => {
+            let stmt = client.prepare_cached(&FUZZY_SEARCH_TITLES).await?;
+            client
+                .query(&stmt, &[&terms.join(" "), &max_age_rating, &limit, &offset])
+                .await?
+        }
+        false => rows,
+    };
+
+    let items = rows
+        .iter()
+        .map(|row| {
+            Ok(SearchHit {
+                title: Title::from_row_ref(row)?,
+                highlight: row.try_get("highlight")?,
+                snippet: row.try_get("snippet")?,
+            })
+        })
+        .collect::<Result<Vec<SearchHit>, MyError>>()?;
+
+    Ok(SearchResults {
+        items,
+        fuzzy,
+        users: None,
+    })
+}
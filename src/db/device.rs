@@ -0,0 +1,259 @@
+use deadpool_postgres::Client;
+use lazy_static::lazy_static;
+use tokio_pg_mapper::FromTokioPostgresRow;
+use tracing::{field::Empty, instrument};
+
+use super::{record_rows, with_fields};
+use crate::{
+    errors::MyError,
+    models::device::{Device, DevicePoll, NewDevice},
+};
+
+/// Added to the polling interval of a device that polls too fast.
+const SLOW_DOWN_SECS: i32 = 5;
+
+lazy_static! {
+    static ref ADD_DEVICE: String = with_fields::<Device>(include_str!("../../sql/add_device.sql"));
+    static ref GET_USER_DEVICES: String =
+        with_fields::<Device>(include_str!("../../sql/get_user_devices.sql"));
+}
+const DELETE_DEVICE: &str = include_str!("../../sql/delete_device.sql");
+const CHECK_DEVICE: &str = include_str!("../../sql/check_device.sql");
+const TOUCH_DEVICE: &str = include_str!("../../sql/touch_device.sql");
+const ADD_DEVICE_CODE: &str = include_str!("../../sql/add_device_code.sql");
+const DELETE_EXPIRED_DEVICE_CODES: &str = include_str!("../../sql/delete_expired_device_codes.sql");
+const APPROVE_DEVICE_CODE: &str = include_str!("../../sql/approve_device_code.sql");
+const GET_DEVICE_CODE: &str = include_str!("../../sql/get_device_code.sql");
+const UPDATE_DEVICE_CODE_POLL: &str = include_str!("../../sql/update_device_code_poll.sql");
+const RESERVE_USER_CODE_ATTEMPT: &str = include_str!("../../sql/reserve_user_code_attempt.sql");
+const RESET_USER_CODE_ATTEMPTS: &str = include_str!("../../sql/reset_user_code_attempts.sql");
+
+pub(super) async fn prepare_statements(client: &Client) -> Result<(), MyError> {
+    let statements = [
+        ADD_DEVICE.as_str(),
+        GET_USER_DEVICES.as_str(),
+        DELETE_DEVICE,
+        CHECK_DEVICE,
+        TOUCH_DEVICE,
+        ADD_DEVICE_CODE,
+        DELETE_EXPIRED_DEVICE_CODES,
+        APPROVE_DEVICE_CODE,
+        GET_DEVICE_CODE,
+        UPDATE_DEVICE_CODE_POLL,
+        RESERVE_USER_CODE_ATTEMPT,
+        RESET_USER_CODE_ATTEMPTS,
+    ];
+
+    for stmt in statements {
+        client.prepare_cached(stmt).await?;
+    }
+
+    Ok(())
+}
+
+#[instrument(level = "debug", skip_all, fields(stmt = "add_device", rows = Empty))]
+pub async fn add_device(
+    client: &Client,
+    user_id: i64,
+    device: &NewDevice,
+) -> Result<Device, MyError> {
+    let stmt = client.prepare_cached(&ADD_DEVICE).await?;
+
+    let row = record_rows(
+        client
+            .query_one(
+                &stmt,
+                &[
+                    &user_id,
+                    &device.model,
+                    &device.firmware_version,
+                    &device.os_version,
+                    &device.app_version,
+                ],
+            )
+            .await?,
+    );
+
+    Ok(Device::from_row_ref(&row)?)
+}
+
+#[instrument(level = "debug", skip_all, fields(stmt = "get_user_devices", rows = Empty))]
+pub async fn get_user_devices(client: &Client, user_id: i64) -> Result<Vec<Device>, MyError> {
+    let stmt = client.prepare_cached(&GET_USER_DEVICES).await?;
+
+    let devices = record_rows(client.query(&stmt, &[&user_id]).await?)
+        .iter()
+        .map(Device::from_row_ref)
+        .collect::<Result<Vec<Device>, _>>()?;
+
+    Ok(devices)
+}
+
+/// Removing a device also deletes its refresh tokens.
+#[instrument(level = "debug", skip_all, fields(stmt = "delete_device", rows = Empty))]
+pub async fn delete_device(client: &Client, device_id: i64, user_id: i64) -> Result<(), MyError> {
+    let stmt = client.prepare_cached(DELETE_DEVICE).await?;
+
+    match record_rows(client.execute(&stmt, &[&device_id, &user_id]).await?) {
+        0 => Err(MyError::NotFound),
+        _ => Ok(()),
+    }
+}
+
+/// Whether the device is still registered to the user. Runs on every
+/// authenticated request, so `last_seen_at` is only written when it is more
+/// than a minute old.
+#[instrument(level = "debug", skip_all, fields(stmt = "check_device", rows = Empty))]
+pub async fn touch_device(client: &Client, device_id: i64, user_id: i64) -> Result<bool, MyError> {
+    let stmt = client.prepare_cached(CHECK_DEVICE).await?;
+
+    let stale: bool = match record_rows(client.query_opt(&stmt, &[&device_id, &user_id]).await?) {
+        Some(row) => row.try_get("stale")?,
+        None => return Ok(false),
+    };
+    if stale {
+        let stmt = client.prepare_cached(TOUCH_DEVICE).await?;
+        client.execute(&stmt, &[&device_id]).await?;
+    }
+
+    Ok(true)
+}
+
+/// Expired codes are kept for a day so late polls still get `expired_token`,
+/// older ones are cleaned up here to free their user codes.
+#[instrument(level = "debug", skip_all, fields(stmt = "add_device_code", rows = Empty))]
+pub async fn add_device_code(
+    client: &Client,
+    device_code_hash: &str,
+    user_code: &str,
+    interval_secs: u64,
+    ttl_secs: u64,
+    device: Option<&NewDevice>,
+) -> Result<(), MyError> {
+    let stmt = client.prepare_cached(DELETE_EXPIRED_DEVICE_CODES).await?;
+    client.execute(&stmt, &[]).await?;
+
+    let stmt = client.prepare_cached(ADD_DEVICE_CODE).await?;
+    record_rows(
+        client
+            .execute(
+                &stmt,
+                &[
+                    &device_code_hash,
+                    &user_code,
+                    &(interval_secs as i32),
+                    &(ttl_secs as f64),
+                    &device.map(|device| &device.model),
+                    &device.map(|device| &device.firmware_version),
+                    &device.map(|device| &device.os_version),
+                    &device.map(|device| &device.app_version),
+                ],
+            )
+            .await?,
+    );
+
+    Ok(())
+}
+
+/// Approves or denies a pending, unexpired code on behalf of `user_id`. The
+/// attempt is counted before the code is looked up, so parallel guesses can't
+/// slip past the limit: `max_attempts` in a row without a matching code lock
+/// the user out for `lock_secs`.
+#[instrument(level = "debug", skip_all, fields(stmt = "approve_device_code", rows = Empty))]
+pub async fn approve_device_code(
+    client: &Client,
+    user_code: &str,
+    user_id: i64,
+    approve: bool,
+    max_attempts: i32,
+    lock_secs: f64,
+) -> Result<(), MyError> {
+    let stmt = client.prepare_cached(RESERVE_USER_CODE_ATTEMPT).await?;
+    let reserved = client
+        .execute(&stmt, &[&user_id, &max_attempts, &lock_secs])
+        .await?;
+    if reserved == 0 {
+        return Err(MyError::UserCodeLocked);
+    }
+
+    let stmt = client.prepare_cached(APPROVE_DEVICE_CODE).await?;
+    let status = if approve { "approved" } else { "denied" };
+
+    match record_rows(
+        client
+            .execute(&stmt, &[&user_code, &user_id, &status])
+            .await?,
+    ) {
+        0 => Err(MyError::NotFound),
+        _ => {
+            let stmt = client.prepare_cached(RESET_USER_CODE_ATTEMPTS).await?;
+            client.execute(&stmt, &[&user_id]).await?;
+            Ok(())
+        }
+    }
+}
+
+/// Records a poll of the device code, enforcing the polling interval. An
+/// approved code is consumed by the poll that returns it, later polls get
+/// `None` like unknown codes.
+#[instrument(level = "debug", skip_all, fields(stmt = "get_device_code", rows = Empty))]
+pub async fn poll_device_code(
+    client: &mut Client,
+    device_code_hash: &str,
+) -> Result<Option<DevicePoll>, MyError> {
+    let transaction = client.transaction().await?;
+
+    let row = match record_rows(
+        transaction
+            .query_opt(
+                &transaction.prepare_cached(GET_DEVICE_CODE).await?,
+                &[&device_code_hash],
+            )
+            .await?,
+    ) {
+        Some(row) => row,
+        None => return Ok(None),
+    };
+    let id: i64 = row.try_get("id")?;
+    let user_id: Option<i64> = row.try_get("user_id")?;
+    let status: String = row.try_get("status")?;
+
+    if row.try_get("expired")? {
+        return Ok(Some(DevicePoll::Expired));
+    }
+
+    let device = match (
+        row.try_get("model")?,
+        row.try_get("firmware_version")?,
+        row.try_get("os_version")?,
+        row.try_get("app_version")?,
+    ) {
+        (Some(model), Some(firmware_version), Some(os_version), Some(app_version)) => {
+            Some(NewDevice {
+                model,
+                firmware_version,
+                os_version,
+                app_version,
+            })
+        }
+        _ => None,
+    };
+
+    let (status, increase, poll) = match (status.as_str(), user_id) {
+        ("consumed", _) => return Ok(None),
+        _ if row.try_get("too_fast")? => (status.as_str(), SLOW_DOWN_SECS, DevicePoll::SlowDown),
+        ("approved", Some(user_id)) => ("consumed", 0, DevicePoll::Approved { user_id, device }),
+        ("denied", _) => ("denied", 0, DevicePoll::Denied),
+        _ => ("pending", 0, DevicePoll::Pending),
+    };
+
+    transaction
+        .execute(
+            &transaction.prepare_cached(UPDATE_DEVICE_CODE_POLL).await?,
+            &[&id, &increase, &status],
+        )
+        .await?;
+    transaction.commit().await?;
+
+    Ok(Some(poll))
+}
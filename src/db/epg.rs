@@ -0,0 +1,189 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+use deadpool_postgres::Client;
+use lazy_static::lazy_static;
+use tokio_pg_mapper::FromTokioPostgresRow;
+use tracing::{field::Empty, instrument, Span};
+
+use super::{record_rows, with_fields};
+use crate::{
+    errors::MyError,
+    models::epg::{
+        Channel, ChannelSchedule, EpgQuery, ImportSummary, NewChannel, NewProgram, Program,
+    },
+};
+
+const DEFAULT_WINDOW_HOURS: i64 = 3;
+const MAX_WINDOW_HOURS: i64 = 24;
+
+lazy_static! {
+    static ref GET_CHANNELS: String =
+        with_fields::<Channel>(include_str!("../../sql/get_channels.sql"));
+    static ref GET_CHANNEL_BY_ID: String =
+        with_fields::<Channel>(include_str!("../../sql/get_channel_by_id.sql"));
+    static ref GET_PROGRAMS: String =
+        with_fields::<Program>(include_str!("../../sql/get_programs.sql"));
+}
+const UPSERT_CHANNEL: &str = include_str!("../../sql/upsert_channel.sql");
+const DELETE_CHANNEL_PROGRAMS: &str = include_str!("../../sql/delete_channel_programs.sql");
+const ADD_CHANNEL_PROGRAMS: &str = include_str!("../../sql/add_channel_programs.sql");
+
+pub(super) async fn prepare_statements(client: &Client) -> Result<(), MyError> {
+    let statements = [
+        GET_CHANNELS.as_str(),
+        GET_CHANNEL_BY_ID.as_str(),
+        GET_PROGRAMS.as_str(),
+        UPSERT_CHANNEL,
+        DELETE_CHANNEL_PROGRAMS,
+        ADD_CHANNEL_PROGRAMS,
+    ];
+
+    for stmt in statements {
+        client.prepare_cached(stmt).await?;
+    }
+
+    Ok(())
+}
+
+/// Upserts the channels by XMLTV id and replaces, per channel, the programs
+/// overlapping the imported time span, so importing a file twice is a no-op.
+#[instrument(level = "debug", skip_all, fields(stmt = "add_channel_programs", rows = Empty))]
+pub async fn import_guide(
+    client: &mut Client,
+    channels: &[NewChannel],
+    programs: &[NewProgram],
+) -> Result<ImportSummary, MyError> {
+    let transaction = client.transaction().await?;
+
+    let stmt = transaction.prepare_cached(UPSERT_CHANNEL).await?;
+    let mut channel_ids = HashMap::new();
+    for channel in channels {
+        let row = transaction
+            .query_one(&stmt, &[&channel.xmltv_id, &channel.name, &channel.icon])
+            .await?;
+        channel_ids.insert(channel.xmltv_id.as_str(), row.try_get::<_, i64>("id")?);
+    }
+
+    let mut schedules: HashMap<i64, Vec<&NewProgram>> = HashMap::new();
+    let mut skipped = 0;
+    for program in programs {
+        match channel_ids.get(program.channel.as_str()) {
+            Some(&channel_id) => schedules.entry(channel_id).or_default().push(program),
+            None => skipped += 1,
+        }
+    }
+
+    let delete_stmt = transaction.prepare_cached(DELETE_CHANNEL_PROGRAMS).await?;
+    let add_stmt = transaction.prepare_cached(ADD_CHANNEL_PROGRAMS).await?;
+    let mut imported = 0;
+    for (channel_id, mut programs) in schedules {
+        // a channel has one program per start time
+        programs.sort_by_key(|program| program.starts_at);
+        programs.dedup_by_key(|program| program.starts_at);
+
+        let span = (
+            programs.first().map(|program| program.starts_at),
+            programs.iter().map(|program| program.ends_at).max(),
+        );
+        if let (Some(from), Some(to)) = span {
+            transaction
+                .execute(&delete_stmt, &[&channel_id, &from, &to])
+                .await?;
+        }
+
+        let starts_at: Vec<DateTime<Utc>> = programs.iter().map(|p| p.starts_at).collect();
+        let ends_at: Vec<DateTime<Utc>> = programs.iter().map(|p| p.ends_at).collect();
+        let titles: Vec<&str> = programs.iter().map(|p| p.title.as_str()).collect();
+        let subtitles: Vec<Option<&str>> = programs.iter().map(|p| p.subtitle.as_deref()).collect();
+        let descriptions: Vec<Option<&str>> =
+            programs.iter().map(|p| p.description.as_deref()).collect();
+        let categories: Vec<Option<&str>> =
+            programs.iter().map(|p| p.category.as_deref()).collect();
+
+        imported += transaction
+            .execute(
+                &add_stmt,
+                &[
+                    &channel_id,
+                    &starts_at,
+                    &ends_at,
+                    &titles,
+                    &subtitles,
+                    &descriptions,
+                    &categories,
+                ],
+            )
+            .await? as usize;
+    }
+
+    transaction.commit().await?;
+    Span::current().record("rows", imported);
+
+    Ok(ImportSummary {
+        channels: channel_ids.len(),
+        programs: imported,
+        skipped,
+    })
+}
+
+/// Programs overlapping the window, grouped by channel. The window starts now
+/// and spans 3 hours unless given, and may not span more than 24 hours.
+#[instrument(level = "debug", skip_all, fields(stmt = "get_programs", rows = Empty))]
+pub async fn get_schedule(
+    client: &Client,
+    query: EpgQuery,
+) -> Result<Vec<ChannelSchedule>, MyError> {
+    let from = query.from.unwrap_or_else(Utc::now);
+    let to = query
+        .to
+        .unwrap_or(from + Duration::hours(DEFAULT_WINDOW_HOURS));
+    if to <= from {
+        return Err(MyError::BadRequest("`to` must be after `from`".into()));
+    }
+    if to - from > Duration::hours(MAX_WINDOW_HOURS) {
+        return Err(MyError::BadRequest(format!(
+            "the window may span at most {} hours",
+            MAX_WINDOW_HOURS
+        )));
+    }
+
+    let channels = match query.channel {
+        Some(channel_id) => {
+            let stmt = client.prepare_cached(&GET_CHANNEL_BY_ID).await?;
+            let row = client
+                .query_opt(&stmt, &[&channel_id])
+                .await?
+                .ok_or(MyError::NotFound)?;
+            vec![Channel::from_row_ref(&row)?]
+        }
+        None => {
+            let stmt = client.prepare_cached(&GET_CHANNELS).await?;
+            client
+                .query(&stmt, &[])
+                .await?
+                .iter()
+                .map(Channel::from_row_ref)
+                .collect::<Result<Vec<Channel>, _>>()?
+        }
+    };
+    let channel_ids: Vec<i64> = channels.iter().map(|channel| channel.id).collect();
+
+    let stmt = client.prepare_cached(&GET_PROGRAMS).await?;
+    let mut programs: HashMap<i64, Vec<Program>> = HashMap::new();
+    for row in record_rows(client.query(&stmt, &[&channel_ids, &from, &to]).await?) {
+        let program = Program::from_row_ref(&row)?;
+        programs
+            .entry(program.channel_id)
+            .or_default()
+            .push(program);
+    }
+
+    Ok(channels
+        .into_iter()
+        .map(|channel| ChannelSchedule {
+            programs: programs.remove(&channel.id).unwrap_or_default(),
+            channel,
+        })
+        .collect())
+}
@@ -0,0 +1,396 @@
+use deadpool_postgres::Client;
+use lazy_static::lazy_static;
+use tokio_pg_mapper::FromTokioPostgresRow;
+use tokio_postgres::{types::ToSql, Row};
+use tracing::{field::Empty, instrument, Span};
+
+pub mod auth;
+pub mod catalog;
+pub mod device;
+pub mod epg;
+pub mod parental;
+pub mod profiles;
+pub mod progress;
+pub mod roles;
+pub mod watchlist;
+
+use crate::{
+    errors::MyError,
+    models::{catalog::SearchQuery, NewUser, UpdateUser, User, UserHit, UserPage, UserQuery},
+};
+
+const DEFAULT_PAGE_SIZE: i64 = 50;
+const MAX_PAGE_SIZE: i64 = 200;
+const SORTABLE_FIELDS: [&str; 5] = ["id", "email", "first_name", "last_name", "username"];
+/// `ORDER BY` of `get_users` without a `sort`, the only order with a cached statement.
+const DEFAULT_USER_ORDER: &str = "id ASC";
+/// Words of a search beyond this are ignored.
+const MAX_SEARCH_TERMS: usize = 8;
+
+// Statement texts are templated once, then prepared once per pooled connection
+// through `prepare_cached`.
+lazy_static! {
+    static ref ADD_USER: String = with_table_fields(include_str!("../../sql/add_user.sql"));
+    static ref GET_USERS: String = with_table_fields(include_str!("../../sql/get_users.sql"));
+    static ref GET_USER_BY_ID: String =
+        with_table_fields(include_str!("../../sql/get_user_by_id.sql"));
+    static ref UPDATE_USER: String = with_table_fields(include_str!("../../sql/update_user.sql"));
+    static ref SEARCH_USERS: String = with_table_fields(include_str!("../../sql/search_users.sql"));
+    static ref FUZZY_SEARCH_USERS: String =
+        with_table_fields(include_str!("../../sql/fuzzy_search_users.sql"));
+}
+const COUNT_USERS: &str = include_str!("../../sql/count_users.sql");
+const DELETE_USER: &str = include_str!("../../sql/delete_user.sql");
+const SEARCH_USERS_EXISTS: &str = include_str!("../../sql/search_users_exists.sql");
+
+fn with_table_fields(stmt: &str) -> String {
+    with_fields::<User>(stmt)
+}
+
+/// Replaces `$table_fields` with the columns mapped by `T`.
+fn with_fields<T: FromTokioPostgresRow>(stmt: &str) -> String {
+    stmt.replace("$table_fields", &T::sql_table_fields())
+}
+
+/// Results a statement call can return, counted as rows for the `rows` span field.
+trait RowCount {
+    fn row_count(&self) -> u64;
+}
+
+impl RowCount for Row {
+    fn row_count(&self) -> u64 {
+        1
+    }
+}
+
+impl RowCount for Option<Row> {
+    fn row_count(&self) -> u64 {
+        self.is_some() as u64
+    }
+}
+
+impl RowCount for Vec<Row> {
+    fn row_count(&self) -> u64 {
+        self.len() as u64
+    }
+}
+
+/// Affected rows, as returned by `execute`.
+impl RowCount for u64 {
+    fn row_count(&self) -> u64 {
+        *self
+    }
+}
+
+/// Records the rows returned or affected by a statement on the span of the
+/// `db` call it runs in, passing the result through.
+fn record_rows<T: RowCount>(rows: T) -> T {
+    Span::current().record("rows", rows.row_count());
+    rows
+}
+
+/// Prepares every statement against the database, so a broken SQL file or a
+/// missing migration fails at boot instead of on the first request.
+pub async fn prepare_statements(client: &Client) -> Result<(), MyError> {
+    let default_users = GET_USERS.replace("$order_by", DEFAULT_USER_ORDER);
+    let statements = [
+        ADD_USER.as_str(),
+        COUNT_USERS,
+        default_users.as_str(),
+        GET_USER_BY_ID.as_str(),
+        UPDATE_USER.as_str(),
+        DELETE_USER,
+        SEARCH_USERS.as_str(),
+        SEARCH_USERS_EXISTS,
+        FUZZY_SEARCH_USERS.as_str(),
+    ];
+
+    for stmt in statements {
+        client.prepare_cached(stmt).await?;
+    }
+    auth::prepare_statements(client).await?;
+    catalog::prepare_statements(client).await?;
+    device::prepare_statements(client).await?;
+    epg::prepare_statements(client).await?;
+    parental::prepare_statements(client).await?;
+    profiles::prepare_statements(client).await?;
+    progress::prepare_statements(client).await?;
+    roles::prepare_statements(client).await?;
+    watchlist::prepare_statements(client).await?;
+
+    Ok(())
+}
+
+#[instrument(level = "debug", skip_all, fields(stmt = "add_user", rows = Empty))]
+pub async fn add_user(
+    client: &Client,
+    user_info: NewUser,
+    password_hash: String,
+) -> Result<User, MyError> {
+    let stmt = client.prepare_cached(&ADD_USER).await?;
+
+    let row = record_rows(
+        client
+            .query_one(
+                &stmt,
+                &[
+                    &user_info.email,
+                    &user_info.first_name,
+                    &user_info.last_name,
+                    &user_info.username,
+                    &password_hash,
+                ],
+            )
+            .await?,
+    );
+
+    Ok(User::from_row_ref(&row)?)
+}
+
+#[instrument(level = "debug", skip_all, fields(stmt = "get_users", rows = Empty))]
+pub async fn get_users(client: &Client, query: UserQuery) -> Result<UserPage, MyError> {
+    let (limit, offset) = page_bounds(query.limit, query.offset)?;
+
+    let sort = parse_sort(query.sort.as_deref().unwrap_or("id"))?;
+
+    // keyset pagination only works when ordering by id alone
+    let keyset = match sort.as_slice() {
+        [("id", ascending)] => Some(*ascending),
+        _ => None,
+    };
+    let (after, before) = match (query.cursor, keyset) {
+        (None, _) => (None, None),
+        (Some(cursor), Some(true)) => (Some(cursor), None),
+        (Some(cursor), Some(false)) => (None, Some(cursor)),
+        (Some(_), None) => {
+            return Err(MyError::BadRequest(
+                "cursor can only be used with sort=id or sort=-id".into(),
+            ))
+        }
+    };
+
+    let mut order_by = sort
+        .iter()
+        .map(|(field, ascending)| format!("{} {}", field, if *ascending { "ASC" } else { "DESC" }))
+        .collect::<Vec<String>>();
+    if !sort.iter().any(|(field, _)| *field == "id") {
+        order_by.push("id ASC".into());
+    }
+
+    let email_pattern = query
+        .email_domain
+        .map(|domain| format!("%@{}", escape_like(&domain)));
+    let name_pattern = query.q.map(|q| format!("%{}%", escape_like(&q)));
+
+    let count_stmt = client.prepare_cached(COUNT_USERS).await?;
+    let total: i64 = client
+        .query_one(
+            &count_stmt,
+            &[&query.username, &email_pattern, &name_pattern],
+        )
+        .await?
+        .get(0);
+
+    let stmt = GET_USERS.replace("$order_by", &order_by.join(", "));
+    let limit_plus_one = limit + 1;
+    let params: [&(dyn ToSql + Sync); 7] = [
+        &query.username,
+        &email_pattern,
+        &name_pattern,
+        &after,
+        &before,
+        // fetch one extra row to find out whether there is a next page
+        &limit_plus_one,
+        &offset,
+    ];
+
+    // only the default order is prepared once per connection, other orders run
+    // as unnamed statements so callers cannot pile up prepared statements
+    let rows = match order_by.as_slice() {
+        [order] if order == DEFAULT_USER_ORDER => {
+            let stmt = client.prepare_cached(&stmt).await?;
+            client.query(&stmt, &params).await?
+        }
+        _ => client.query(stmt.as_str(), &params).await?,
+    };
+    let mut items = record_rows(rows)
+        .iter()
+        .map(User::from_row_ref)
+        .collect::<Result<Vec<User>, _>>()?;
+
+    let next_cursor = if items.len() as i64 > limit {
+        items.truncate(limit as usize);
+        keyset.and(items.last().map(|user| user.id))
+    } else {
+        None
+    };
+
+    Ok(UserPage {
+        items,
+        total,
+        next_cursor,
+    })
+}
+
+/// Matches every word of `q` as a prefix of the username or name, ranking
+/// username matches first. When nothing matches, falls back to users whose
+/// username or name is similar to `q`, like `catalog::search_titles`.
+#[instrument(level = "debug", skip_all, fields(stmt = "search_users", rows = Empty))]
+pub async fn search_users(client: &Client, query: &SearchQuery) -> Result<Vec<UserHit>, MyError> {
+    let (limit, offset) = page_bounds(query.limit, query.offset)?;
+    let terms = search_terms(&query.q)?;
+    let prefix_query = prefix_query(&terms);
+
+    let stmt = client.prepare_cached(&SEARCH_USERS).await?;
+    let rows = record_rows(
+        client
+            .query(&stmt, &[&prefix_query, &limit, &offset])
+            .await?,
+    );
+
+    // an empty page past the first one may just be the end of the matches
+    let fuzzy = rows.is_empty()
+        && (offset == 0 || {
+            let stmt = client.prepare_cached(SEARCH_USERS_EXISTS).await?;
+            let row = client.query_one(&stmt, &[&prefix_query]).await?;
+            !row.try_get::<_, bool>("found")?
+        });
+    let rows = match fuzzy {
+        true => {
+            let stmt = client.prepare_cached(&FUZZY_SEARCH_USERS).await?;
+            client
+                .query(&stmt, &[&terms.join(" "), &limit, &offset])
+                .await?
+        }
+        false => rows,
+    };
+
+    rows.iter()
+        .map(|row| {
+            Ok(UserHit {
+                user: User::from_row_ref(row)?,
+                highlight: row.try_get("highlight")?,
+            })
+        })
+        .collect()
+}
+
+/// Splits a search into lowercase words, anything but letters and digits separates them.
+fn search_terms(q: &str) -> Result<Vec<String>, MyError> {
+    let terms: Vec<String> = q
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|term| !term.is_empty())
+        .take(MAX_SEARCH_TERMS)
+        .map(str::to_lowercase)
+        .collect();
+    if terms.is_empty() {
+        return Err(MyError::BadRequest(
+            "q must contain at least one letter or digit".into(),
+        ));
+    }
+    Ok(terms)
+}
+
+/// `["quiet", "se"]` becomes `quiet:* & se:*`.
+fn prefix_query(terms: &[String]) -> String {
+    terms
+        .iter()
+        .map(|term| format!("{}:*", term))
+        .collect::<Vec<String>>()
+        .join(" & ")
+}
+
+/// Validates `limit` and `offset` query parameters, applying the defaults.
+fn page_bounds(limit: Option<i64>, offset: Option<i64>) -> Result<(i64, i64), MyError> {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE);
+    if !(1..=MAX_PAGE_SIZE).contains(&limit) {
+        return Err(MyError::BadRequest(format!(
+            "limit must be between 1 and {}",
+            MAX_PAGE_SIZE
+        )));
+    }
+    let offset = offset.unwrap_or(0);
+    if offset < 0 {
+        return Err(MyError::BadRequest("offset must not be negative".into()));
+    }
+    Ok((limit, offset))
+}
+
+/// Parses `username,-email` into `[("username", true), ("email", false)]`.
+/// Each field may appear once.
+fn parse_sort(sort: &str) -> Result<Vec<(&'static str, bool)>, MyError> {
+    let mut keys: Vec<(&'static str, bool)> = Vec::new();
+    for key in sort.split(',') {
+        let (name, ascending) = match key.trim().strip_prefix('-') {
+            Some(name) => (name, false),
+            None => (key.trim(), true),
+        };
+        let field = SORTABLE_FIELDS
+            .iter()
+            .find(|field| **field == name)
+            .ok_or_else(|| MyError::BadRequest(format!("cannot sort by '{}'", name)))?;
+        if keys.iter().any(|(sorted, _)| sorted == field) {
+            return Err(MyError::BadRequest(format!(
+                "cannot sort by '{}' more than once",
+                name
+            )));
+        }
+        keys.push((field, ascending));
+    }
+    Ok(keys)
+}
+
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+#[instrument(level = "debug", skip_all, fields(stmt = "get_user_by_id", rows = Empty))]
+pub async fn get_user_by_id(client: &Client, user_id: i64) -> Result<User, MyError> {
+    let stmt = client.prepare_cached(&GET_USER_BY_ID).await?;
+
+    let row = record_rows(client.query_opt(&stmt, &[&user_id]).await?).ok_or(MyError::NotFound)?;
+
+    Ok(User::from_row_ref(&row)?)
+}
+
+#[instrument(level = "debug", skip_all, fields(stmt = "update_user", rows = Empty))]
+pub async fn update_user(
+    client: &Client,
+    user_id: i64,
+    user_info: UpdateUser,
+    password_hash: Option<String>,
+) -> Result<User, MyError> {
+    let stmt = client.prepare_cached(&UPDATE_USER).await?;
+
+    let row = record_rows(
+        client
+            .query_opt(
+                &stmt,
+                &[
+                    &user_id,
+                    &user_info.email,
+                    &user_info.first_name,
+                    &user_info.last_name,
+                    &user_info.username,
+                    &password_hash,
+                ],
+            )
+            .await?,
+    )
+    .ok_or(MyError::NotFound)?;
+
+    Ok(User::from_row_ref(&row)?)
+}
+
+#[instrument(level = "debug", skip_all, fields(stmt = "delete_user", rows = Empty))]
+pub async fn delete_user(client: &Client, user_id: i64) -> Result<(), MyError> {
+    let stmt = client.prepare_cached(DELETE_USER).await?;
+
+    match record_rows(client.execute(&stmt, &[&user_id]).await?) {
+        0 => Err(MyError::NotFound),
+        _ => Ok(()),
+    }
+}
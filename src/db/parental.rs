@@ -0,0 +1,82 @@
+use deadpool_postgres::Client;
+use tracing::{field::Empty, instrument};
+
+use super::record_rows;
+use crate::errors::MyError;
+
+const HAS_PARENTAL_PIN: &str = include_str!("../../sql/has_parental_pin.sql");
+const SET_PARENTAL_PIN: &str = include_str!("../../sql/set_parental_pin.sql");
+const RESERVE_PIN_ATTEMPT: &str = include_str!("../../sql/reserve_pin_attempt.sql");
+const RESET_PIN_ATTEMPTS: &str = include_str!("../../sql/reset_pin_attempts.sql");
+
+pub(super) async fn prepare_statements(client: &Client) -> Result<(), MyError> {
+    let statements = [
+        HAS_PARENTAL_PIN,
+        SET_PARENTAL_PIN,
+        RESERVE_PIN_ATTEMPT,
+        RESET_PIN_ATTEMPTS,
+    ];
+
+    for stmt in statements {
+        client.prepare_cached(stmt).await?;
+    }
+
+    Ok(())
+}
+
+/// An unknown user is `NotFound`.
+#[instrument(level = "debug", skip_all, fields(stmt = "has_parental_pin", rows = Empty))]
+pub async fn has_parental_pin(client: &Client, user_id: i64) -> Result<bool, MyError> {
+    let stmt = client.prepare_cached(HAS_PARENTAL_PIN).await?;
+
+    let row = record_rows(client.query_opt(&stmt, &[&user_id]).await?).ok_or(MyError::NotFound)?;
+
+    Ok(row.try_get("has_pin")?)
+}
+
+/// Also clears any lockout.
+#[instrument(level = "debug", skip_all, fields(stmt = "set_parental_pin", rows = Empty))]
+pub async fn set_parental_pin(
+    client: &Client,
+    user_id: i64,
+    pin_hash: &str,
+) -> Result<(), MyError> {
+    let stmt = client.prepare_cached(SET_PARENTAL_PIN).await?;
+
+    match record_rows(client.execute(&stmt, &[&user_id, &pin_hash]).await?) {
+        0 => Err(MyError::NotFound),
+        _ => Ok(()),
+    }
+}
+
+/// Counts an attempt before the PIN is checked, so concurrent guesses cannot
+/// slip past the limit, and returns the PIN hash to check against. The
+/// `max_attempts`th attempt in a row without a reset locks the PIN for
+/// `lock_secs`, `None` means it is locked (or was removed meanwhile).
+#[instrument(level = "debug", skip_all, fields(stmt = "reserve_pin_attempt", rows = Empty))]
+pub async fn reserve_pin_attempt(
+    client: &Client,
+    user_id: i64,
+    max_attempts: i32,
+    lock_secs: f64,
+) -> Result<Option<String>, MyError> {
+    let stmt = client.prepare_cached(RESERVE_PIN_ATTEMPT).await?;
+
+    let row = record_rows(
+        client
+            .query_opt(&stmt, &[&user_id, &max_attempts, &lock_secs])
+            .await?,
+    );
+
+    Ok(row.map(|row| row.get("parental_pin_hash")))
+}
+
+/// Called after a correct PIN, clears the attempt count and any lockout.
+#[instrument(level = "debug", skip_all, fields(stmt = "reset_pin_attempts", rows = Empty))]
+pub async fn reset_pin_attempts(client: &Client, user_id: i64) -> Result<(), MyError> {
+    let stmt = client.prepare_cached(RESET_PIN_ATTEMPTS).await?;
+
+    record_rows(client.execute(&stmt, &[&user_id]).await?);
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use deadpool_postgres::Client;
+use lazy_static::lazy_static;
+use tokio_pg_mapper::FromTokioPostgresRow;
+use tracing::{field::Empty, instrument};
+
+use super::{record_rows, with_fields};
+use crate::{
+    errors::MyError,
+    models::profiles::{NewProfile, Profile, UpdateProfile},
+};
+
+lazy_static! {
+    static ref ADD_PROFILE: String =
+        with_fields::<Profile>(include_str!("../../sql/add_profile.sql"));
+    static ref GET_USER_PROFILES: String =
+        with_fields::<Profile>(include_str!("../../sql/get_user_profiles.sql"));
+    static ref GET_PROFILE: String =
+        with_fields::<Profile>(include_str!("../../sql/get_profile.sql"));
+    static ref UPDATE_PROFILE: String =
+        with_fields::<Profile>(include_str!("../../sql/update_profile.sql"));
+}
+const DELETE_PROFILE: &str = include_str!("../../sql/delete_profile.sql");
+
+pub(super) async fn prepare_statements(client: &Client) -> Result<(), MyError> {
+    let statements = [
+        ADD_PROFILE.as_str(),
+        GET_USER_PROFILES.as_str(),
+        GET_PROFILE.as_str(),
+        UPDATE_PROFILE.as_str(),
+        DELETE_PROFILE,
+    ];
+
+    for stmt in statements {
+        client.prepare_cached(stmt).await?;
+    }
+
+    Ok(())
+}
+
+#[instrument(level = "debug", skip_all, fields(stmt = "add_profile", rows = Empty))]
+pub async fn add_profile(
+    client: &Client,
+    user_id: i64,
+    profile: NewProfile,
+) -> Result<Profile, MyError> {
+    let stmt = client.prepare_cached(&ADD_PROFILE).await?;
+
+    let row = record_rows(
+        client
+            .query_one(
+                &stmt,
+                &[
+                    &user_id,
+                    &profile.name,
+                    &profile.avatar,
+                    &profile.is_kids,
+                    &profile.language,
+                    &profile.max_age_rating,
+                ],
+            )
+            .await?,
+    );
+
+    Ok(Profile::from_row_ref(&row)?)
+}
+
+#[instrument(level = "debug", skip_all, fields(stmt = "get_user_profiles", rows = Empty))]
+pub async fn get_user_profiles(client: &Client, user_id: i64) -> Result<Vec<Profile>, MyError> {
+    let stmt = client.prepare_cached(&GET_USER_PROFILES).await?;
+
+    let profiles = record_rows(client.query(&stmt, &[&user_id]).await?)
+        .iter()
+        .map(Profile::from_row_ref)
+        .collect::<Result<Vec<Profile>, _>>()?;
+
+    Ok(profiles)
+}
+
+/// Profiles of other users are `NotFound`.
+#[instrument(level = "debug", skip_all, fields(stmt = "get_profile", rows = Empty))]
+pub async fn get_profile(
+    client: &Client,
+    profile_id: i64,
+    user_id: i64,
+) -> Result<Profile, MyError> {
+    let stmt = client.prepare_cached(&GET_PROFILE).await?;
+
+    let row = record_rows(client.query_opt(&stmt, &[&profile_id, &user_id]).await?)
+        .ok_or(MyError::NotFound)?;
+
+    Ok(Profile::from_row_ref(&row)?)
+}
+
+#[instrument(level = "debug", skip_all, fields(stmt = "update_profile", rows = Empty))]
+pub async fn update_profile(
+    client: &Client,
+    profile_id: i64,
+    user_id: i64,
+    profile: UpdateProfile,
+) -> Result<Profile, MyError> {
+    let stmt = client.prepare_cached(&UPDATE_PROFILE).await?;
+
+    let row = record_rows(
+        client
+            .query_opt(
+                &stmt,
+                &[
+                    &profile_id,
+                    &user_id,
+                    &profile.name,
+                    &profile.avatar,
+                    &profile.is_kids,
+                    &profile.language,
+                    &profile.max_age_rating,
+                ],
+            )
+            .await?,
+    )
+    .ok_or(MyError::NotFound)?;
+
+    Ok(Profile::from_row_ref(&row)?)
+}
+
+#[instrument(level = "debug", skip_all, fields(stmt = "delete_profile", rows = Empty))]
+pub async fn delete_profile(client: &Client, profile_id: i64, user_id: i64) -> Result<(), MyError> {
+    let stmt = client.prepare_cached(DELETE_PROFILE).await?;
+
+    match record_rows(client.execute(&stmt, &[&profile_id, &user_id]).await?) {
+        0 => Err(MyError::NotFound),
+        _ => Ok(()),
+    }
+}
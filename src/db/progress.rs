@@ -0,0 +1,91 @@
+use deadpool_postgres::Client;
+use lazy_static::lazy_static;
+use tokio_pg_mapper::FromTokioPostgresRow;
+use tracing::{field::Empty, instrument};
+
+use super::{page_bounds, record_rows};
+use crate::{
+    errors::MyError,
+    models::{
+        catalog::Title,
+        progress::{ContinueWatchingItem, NewProgress, WatchProgress},
+    },
+};
+
+/// Share of the duration after which a movie or episode counts as watched,
+/// so the end credits don't keep it in continue watching.
+const COMPLETED_THRESHOLD: f64 = 0.9;
+
+lazy_static! {
+    static ref GET_CONTINUE_WATCHING: String = include_str!("../../sql/get_continue_watching.sql")
+        .replace("$progress_fields", &WatchProgress::sql_table_fields())
+        .replace("$title_fields", &Title::sql_table_fields());
+}
+const SAVE_PROGRESS: &str = include_str!("../../sql/save_progress.sql");
+
+pub(super) async fn prepare_statements(client: &Client) -> Result<(), MyError> {
+    let statements = [SAVE_PROGRESS, GET_CONTINUE_WATCHING.as_str()];
+
+    for stmt in statements {
+        client.prepare_cached(stmt).await?;
+    }
+
+    Ok(())
+}
+
+/// Upserts the position in a single statement, as TVs report it every few
+/// seconds. Unknown profiles of the user, titles and episodes are `NotFound`,
+/// as are titles above the profile's age rating limit.
+#[instrument(level = "debug", skip_all, fields(stmt = "save_progress", rows = Empty))]
+pub async fn save_progress(
+    client: &Client,
+    profile_id: i64,
+    user_id: i64,
+    progress: &NewProgress,
+) -> Result<(), MyError> {
+    let stmt = client.prepare_cached(SAVE_PROGRESS).await?;
+
+    let saved = record_rows(
+        client
+            .execute(
+                &stmt,
+                &[
+                    &profile_id,
+                    &user_id,
+                    &progress.title_id,
+                    &progress.episode_id,
+                    &progress.position_secs,
+                    &progress.duration_secs,
+                    &COMPLETED_THRESHOLD,
+                ],
+            )
+            .await?,
+    );
+
+    match saved {
+        0 => Err(MyError::NotFound),
+        _ => Ok(()),
+    }
+}
+
+/// Most recently watched, unfinished item of each title, latest first.
+#[instrument(level = "debug", skip_all, fields(stmt = "get_continue_watching", rows = Empty))]
+pub async fn get_continue_watching(
+    client: &Client,
+    profile_id: i64,
+    limit: Option<i64>,
+) -> Result<Vec<ContinueWatchingItem>, MyError> {
+    let (limit, _) = page_bounds(limit, None)?;
+
+    let stmt = client.prepare_cached(&GET_CONTINUE_WATCHING).await?;
+
+    record_rows(client.query(&stmt, &[&profile_id, &limit]).await?)
+        .iter()
+        .map(|row| {
+            Ok(ContinueWatchingItem {
+                progress: WatchProgress::from_row_ref(row)?,
+                title: Title::from_row_ref(row)?,
+            })
+        })
+        .collect()
+}
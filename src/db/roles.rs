@@ -0,0 +1,93 @@
+use deadpool_postgres::Client;
+use tokio_pg_mapper::FromTokioPostgresRow;
+use tokio_postgres::error::SqlState;
+use tracing::{field::Empty, instrument};
+
+use super::record_rows;
+use crate::{errors::MyError, models::roles::Role};
+
+const GET_USER_PERMISSION: &str = include_str!("../../sql/get_user_permission.sql");
+const GET_ROLES: &str = include_str!("../../sql/get_roles.sql");
+const GET_USER_ROLES: &str = include_str!("../../sql/get_user_roles.sql");
+const ADD_USER_ROLE: &str = include_str!("../../sql/add_user_role.sql");
+const DELETE_USER_ROLE: &str = include_str!("../../sql/delete_user_role.sql");
+
+pub(super) async fn prepare_statements(client: &Client) -> Result<(), MyError> {
+    let statements = [
+        GET_USER_PERMISSION,
+        GET_ROLES,
+        GET_USER_ROLES,
+        ADD_USER_ROLE,
+        DELETE_USER_ROLE,
+    ];
+
+    for stmt in statements {
+        client.prepare_cached(stmt).await?;
+    }
+
+    Ok(())
+}
+
+/// Whether any role of the user grants `permission`, unknown users have none.
+#[instrument(level = "debug", skip_all, fields(stmt = "get_user_permission", rows = Empty))]
+pub async fn has_permission(
+    client: &Client,
+    user_id: i64,
+    permission: &str,
+) -> Result<bool, MyError> {
+    let stmt = client.prepare_cached(GET_USER_PERMISSION).await?;
+
+    let row = record_rows(client.query_one(&stmt, &[&user_id, &permission]).await?);
+
+    Ok(row.try_get("granted")?)
+}
+
+#[instrument(level = "debug", skip_all, fields(stmt = "get_roles", rows = Empty))]
+pub async fn get_roles(client: &Client) -> Result<Vec<Role>, MyError> {
+    let stmt = client.prepare_cached(GET_ROLES).await?;
+
+    let roles = record_rows(client.query(&stmt, &[]).await?)
+        .iter()
+        .map(Role::from_row_ref)
+        .collect::<Result<Vec<Role>, _>>()?;
+
+    Ok(roles)
+}
+
+#[instrument(level = "debug", skip_all, fields(stmt = "get_user_roles", rows = Empty))]
+pub async fn get_user_roles(client: &Client, user_id: i64) -> Result<Vec<Role>, MyError> {
+    let stmt = client.prepare_cached(GET_USER_ROLES).await?;
+
+    let roles = record_rows(client.query(&stmt, &[&user_id]).await?)
+        .iter()
+        .map(Role::from_row_ref)
+        .collect::<Result<Vec<Role>, _>>()?;
+
+    Ok(roles)
+}
+
+/// Granting a role the user already has is a no-op, an unknown user or role
+/// is `NotFound`.
+#[instrument(level = "debug", skip_all, fields(stmt = "add_user_role", rows = Empty))]
+pub async fn add_user_role(client: &Client, user_id: i64, role: &str) -> Result<(), MyError> {
+    let stmt = client.prepare_cached(ADD_USER_ROLE).await?;
+
+    match client.execute(&stmt, &[&user_id, &role]).await {
+        Ok(rows) => {
+            record_rows(rows);
+            Ok(())
+        }
+        Err(err) if err.code() == Some(&SqlState::FOREIGN_KEY_VIOLATION) => Err(MyError::NotFound),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[instrument(level = "debug", skip_all, fields(stmt = "delete_user_role", rows = Empty))]
+pub async fn delete_user_role(client: &Client, user_id: i64, role: &str) -> Result<(), MyError> {
+    let stmt = client.prepare_cached(DELETE_USER_ROLE).await?;
+
+    match record_rows(client.execute(&stmt, &[&user_id, &role]).await?) {
+        0 => Err(MyError::NotFound),
+        _ => Ok(()),
+    }
+}
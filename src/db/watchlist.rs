@@ -0,0 +1,168 @@
+use deadpool_postgres::{Client, Transaction};
+use lazy_static::lazy_static;
+use tokio_pg_mapper::FromTokioPostgresRow;
+use tokio_postgres::error::SqlState;
+use tracing::{field::Empty, instrument};
+
+use super::{page_bounds, record_rows, with_fields};
+use crate::{
+    errors::MyError,
+    models::{
+        catalog::Title,
+        watchlist::{WatchlistEntry, WatchlistItem, WatchlistPage, WatchlistQuery},
+    },
+};
+
+lazy_static! {
+    static ref ADD_WATCHLIST_ENTRY: String =
+        with_fields::<WatchlistEntry>(include_str!("../../sql/add_watchlist_entry.sql"));
+    static ref GET_WATCHLIST: String = include_str!("../../sql/get_watchlist.sql")
+        .replace("$watchlist_fields", &WatchlistEntry::sql_table_fields())
+        .replace("$title_fields", &Title::sql_table_fields());
+}
+const COUNT_WATCHLIST: &str = include_str!("../../sql/count_watchlist.sql");
+const MOVE_WATCHLIST_ENTRY: &str = include_str!("../../sql/move_watchlist_entry.sql");
+const DELETE_WATCHLIST_ENTRY: &str = include_str!("../../sql/delete_watchlist_entry.sql");
+const LOCK_WATCHLIST: &str = include_str!("../../sql/lock_watchlist.sql");
+
+pub(super) async fn prepare_statements(client: &Client) -> Result<(), MyError> {
+    let default_watchlist = watchlist_stmt("position")?;
+    let statements = [
+        ADD_WATCHLIST_ENTRY.as_str(),
+        default_watchlist.as_str(),
+        COUNT_WATCHLIST,
+        MOVE_WATCHLIST_ENTRY,
+        DELETE_WATCHLIST_ENTRY,
+        LOCK_WATCHLIST,
+    ];
+
+    for stmt in statements {
+        client.prepare_cached(stmt).await?;
+    }
+
+    Ok(())
+}
+
+/// One cached statement per sort order.
+fn watchlist_stmt(sort: &str) -> Result<String, MyError> {
+    let order_by = match sort {
+        "position" => "watchlist.position ASC",
+        "added_at" => "watchlist.added_at ASC, watchlist.title_id ASC",
+        "-added_at" => "watchlist.added_at DESC, watchlist.title_id DESC",
+        _ => return Err(MyError::BadRequest(format!("cannot sort by '{}'", sort))),
+    };
+    Ok(GET_WATCHLIST.replace("$order_by", order_by))
+}
+
+/// Positions are computed from the entries already there, so changes to a
+/// profile's watchlist run one at a time, holding a lock on the profile.
+async fn lock_watchlist(transaction: &Transaction<'_>, profile_id: i64) -> Result<(), MyError> {
+    let stmt = transaction.prepare_cached(LOCK_WATCHLIST).await?;
+    transaction.query_opt(&stmt, &[&profile_id]).await?;
+    Ok(())
+}
+
+/// Appends the title to the end of the watchlist, titles that are unknown or
+/// above the profile's age rating limit are `NotFound`.
+#[instrument(level = "debug", skip_all, fields(stmt = "add_watchlist_entry", rows = Empty))]
+pub async fn add_watchlist_entry(
+    client: &mut Client,
+    profile_id: i64,
+    title_id: i64,
+) -> Result<WatchlistEntry, MyError> {
+    let transaction = client.transaction().await?;
+    lock_watchlist(&transaction, profile_id).await?;
+
+    let stmt = transaction.prepare_cached(&ADD_WATCHLIST_ENTRY).await?;
+    let row = match transaction
+        .query_opt(&stmt, &[&profile_id, &title_id])
+        .await
+    {
+        Ok(row) => record_rows(row).ok_or(MyError::NotFound)?,
+        Err(err) if err.code() == Some(&SqlState::UNIQUE_VIOLATION) => {
+            return Err(MyError::Conflict(
+                "title is already in the watchlist".into(),
+            ))
+        }
+        Err(err) => return Err(err.into()),
+    };
+    transaction.commit().await?;
+
+    Ok(WatchlistEntry::from_row_ref(&row)?)
+}
+
+#[instrument(level = "debug", skip_all, fields(stmt = "get_watchlist", rows = Empty))]
+pub async fn get_watchlist(
+    client: &Client,
+    profile_id: i64,
+    query: WatchlistQuery,
+) -> Result<WatchlistPage, MyError> {
+    let (limit, offset) = page_bounds(query.limit, query.offset)?;
+    let stmt = watchlist_stmt(query.sort.as_deref().unwrap_or("position"))?;
+
+    let count_stmt = client.prepare_cached(COUNT_WATCHLIST).await?;
+    let total: i64 = client.query_one(&count_stmt, &[&profile_id]).await?.get(0);
+
+    let stmt = client.prepare_cached(&stmt).await?;
+    let items = record_rows(client.query(&stmt, &[&profile_id, &limit, &offset]).await?)
+        .iter()
+        .map(|row| {
+            Ok(WatchlistItem {
+                entry: WatchlistEntry::from_row_ref(row)?,
+                title: Title::from_row_ref(row)?,
+            })
+        })
+        .collect::<Result<Vec<WatchlistItem>, MyError>>()?;
+
+    Ok(WatchlistPage { items, total })
+}
+
+/// Moves an entry to `position`, shifting the entries in between.
+#[instrument(level = "debug", skip_all, fields(stmt = "move_watchlist_entry", rows = Empty))]
+pub async fn move_watchlist_entry(
+    client: &mut Client,
+    profile_id: i64,
+    title_id: i64,
+    position: i32,
+) -> Result<(), MyError> {
+    let transaction = client.transaction().await?;
+    lock_watchlist(&transaction, profile_id).await?;
+
+    let stmt = transaction.prepare_cached(MOVE_WATCHLIST_ENTRY).await?;
+    let moved = record_rows(
+        transaction
+            .execute(&stmt, &[&profile_id, &title_id, &position])
+            .await?,
+    );
+    transaction.commit().await?;
+
+    match moved {
+        0 => Err(MyError::NotFound),
+        _ => Ok(()),
+    }
+}
+
+/// Removes an entry, closing the gap it leaves in the positions.
+#[instrument(level = "debug", skip_all, fields(stmt = "delete_watchlist_entry", rows = Empty))]
+pub async fn delete_watchlist_entry(
+    client: &mut Client,
+    profile_id: i64,
+    title_id: i64,
+) -> Result<(), MyError> {
+    let transaction = client.transaction().await?;
+    lock_watchlist(&transaction, profile_id).await?;
+
+    let stmt = transaction.prepare_cached(DELETE_WATCHLIST_ENTRY).await?;
+    let removed: i64 = record_rows(
+        transaction
+            .query_one(&stmt, &[&profile_id, &title_id])
+            .await?,
+    )
+    .try_get("removed")?;
+    transaction.commit().await?;
+
+    match removed {
+        0 => Err(MyError::NotFound),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,303 @@
+use actix_web::{
+    http::{header, StatusCode},
+    HttpResponse, ResponseError,
+};
+use argon2::password_hash::Error as HashError;
+use deadpool_postgres::PoolError;
+use derive_more::{Display, From};
+use jsonwebtoken::errors::Error as JwtError;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio_pg_mapper::Error as PGMError;
+use tokio_postgres::error::{Error as PGError, SqlState};
+use utoipa::ToSchema;
+use validator::ValidationErrors;
+
+#[derive(Display, From, Debug)]
+pub enum MyError {
+    NotFound,
+    InvalidCredentials,
+    InvalidRefreshToken,
+    Unauthorized,
+    Forbidden,
+    AuthorizationPending,
+    SlowDown,
+    AccessDenied,
+    ExpiredToken,
+    InvalidDeviceCode,
+    UnsupportedGrantType,
+    UserCodeLocked,
+    PinRequired,
+    InvalidPin,
+    PinLocked,
+    PasswordRequired,
+    InvalidPassword,
+    #[from(ignore)]
+    BadRequest(String),
+    #[from(ignore)]
+    Conflict(String),
+    PGError(PGError),
+    PGMError(PGMError),
+    PoolError(PoolError),
+    ValidationError(ValidationErrors),
+    #[from(ignore)]
+    MigrationError(String),
+    HashError(HashError),
+    JwtError(JwtError),
+}
+impl std::error::Error for MyError {}
+
+/// Body of every error response, `code` is stable and meant for clients to match on.
+#[derive(Serialize, ToSchema)]
+pub struct ErrorBody {
+    #[serde(skip)]
+    status: StatusCode,
+    code: &'static str,
+    message: String,
+    details: Option<Value>,
+}
+
+impl ErrorBody {
+    fn new(status: StatusCode, code: &'static str, message: impl Into<String>) -> Self {
+        ErrorBody {
+            status,
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    fn with_details(mut self, details: Value) -> Self {
+        self.details = Some(details);
+        self
+    }
+
+    fn internal() -> Self {
+        ErrorBody::new(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "internal_error",
+            "internal server error",
+        )
+    }
+
+    fn unavailable() -> Self {
+        ErrorBody::new(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "database_unavailable",
+            "database is currently unavailable",
+        )
+    }
+}
+
+impl MyError {
+    fn body(&self) -> ErrorBody {
+        match *self {
+            MyError::NotFound => {
+                ErrorBody::new(StatusCode::NOT_FOUND, "not_found", "resource not found")
+            }
+            MyError::InvalidCredentials => ErrorBody::new(
+                StatusCode::UNAUTHORIZED,
+                "invalid_credentials",
+                "username or password is incorrect",
+            ),
+            MyError::InvalidRefreshToken => ErrorBody::new(
+                StatusCode::UNAUTHORIZED,
+                "invalid_refresh_token",
+                "refresh token is invalid, expired or revoked",
+            ),
+            MyError::Unauthorized => ErrorBody::new(
+                StatusCode::UNAUTHORIZED,
+                "unauthorized",
+                "a valid bearer token is required",
+            ),
+            MyError::Forbidden => ErrorBody::new(
+                StatusCode::FORBIDDEN,
+                "forbidden",
+                "you are not allowed to access this resource",
+            ),
+            // device flow errors use the RFC 8628 codes
+            MyError::AuthorizationPending => ErrorBody::new(
+                StatusCode::BAD_REQUEST,
+                "authorization_pending",
+                "the user has not approved the device yet",
+            ),
+            MyError::SlowDown => ErrorBody::new(
+                StatusCode::BAD_REQUEST,
+                "slow_down",
+                "polling too fast, increase the interval by 5 seconds",
+            ),
+            MyError::AccessDenied => ErrorBody::new(
+                StatusCode::BAD_REQUEST,
+                "access_denied",
+                "the user denied the device",
+            ),
+            MyError::ExpiredToken => ErrorBody::new(
+                StatusCode::BAD_REQUEST,
+                "expired_token",
+                "the device code has expired, request a new one",
+            ),
+            MyError::InvalidDeviceCode => ErrorBody::new(
+                StatusCode::BAD_REQUEST,
+                "invalid_grant",
+                "device code is invalid or was already used",
+            ),
+            MyError::UnsupportedGrantType => ErrorBody::new(
+                StatusCode::BAD_REQUEST,
+                "unsupported_grant_type",
+                "grant_type must be urn:ietf:params:oauth:grant-type:device_code",
+            ),
+            MyError::UserCodeLocked => ErrorBody::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                "user_code_locked",
+                "too many incorrect codes, try again later",
+            ),
+            MyError::PinRequired => ErrorBody::new(
+                StatusCode::FORBIDDEN,
+                "pin_required",
+                "the parental PIN is required for this change",
+            ),
+            MyError::InvalidPin => ErrorBody::new(
+                StatusCode::FORBIDDEN,
+                "invalid_pin",
+                "the parental PIN is incorrect",
+            ),
+            MyError::PinLocked => ErrorBody::new(
+                StatusCode::TOO_MANY_REQUESTS,
+                "pin_locked",
+                "too many incorrect PIN attempts, try again later",
+            ),
+            MyError::PasswordRequired => ErrorBody::new(
+                StatusCode::FORBIDDEN,
+                "password_required",
+                "the current password is required to change the password",
+            ),
+            MyError::InvalidPassword => ErrorBody::new(
+                StatusCode::FORBIDDEN,
+                "invalid_password",
+                "the current password is incorrect",
+            ),
+            MyError::BadRequest(ref msg) => {
+                ErrorBody::new(StatusCode::BAD_REQUEST, "bad_request", msg.clone())
+            }
+            MyError::Conflict(ref msg) => {
+                ErrorBody::new(StatusCode::CONFLICT, "conflict", msg.clone())
+            }
+            MyError::PGError(ref err) => pg_error_body(err),
+            MyError::PGMError(_) => ErrorBody::internal(),
+            MyError::PoolError(PoolError::Timeout(_))
+            | MyError::PoolError(PoolError::Backend(_))
+            | MyError::PoolError(PoolError::Closed) => ErrorBody::unavailable(),
+            MyError::PoolError(_)
+            | MyError::MigrationError(_)
+            | MyError::HashError(_)
+            | MyError::JwtError(_) => ErrorBody::internal(),
+            MyError::ValidationError(ref errors) => ErrorBody::new(
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "validation_failed",
+                "request payload is invalid",
+            )
+            .with_details(validation_details(errors)),
+        }
+    }
+}
+
+fn pg_error_body(err: &PGError) -> ErrorBody {
+    let db_error = match err.as_db_error() {
+        Some(db_error) => db_error,
+        None if err.is_closed() => return ErrorBody::unavailable(),
+        None => return ErrorBody::internal(),
+    };
+
+    match *db_error.code() {
+        SqlState::UNIQUE_VIOLATION => ErrorBody::new(
+            StatusCode::CONFLICT,
+            "conflict",
+            "a record with the same value already exists",
+        )
+        .with_details(json!({
+            "field": db_error.detail().and_then(key_column),
+            "constraint": db_error.constraint(),
+        })),
+        SqlState::NOT_NULL_VIOLATION => ErrorBody::new(
+            StatusCode::BAD_REQUEST,
+            "missing_field",
+            "a required field is missing",
+        )
+        .with_details(json!({ "field": db_error.column() })),
+        _ => ErrorBody::internal(),
+    }
+}
+
+/// Maps every invalid field to its list of messages, e.g. `{"email": ["must be a valid email address"]}`.
+fn validation_details(errors: &ValidationErrors) -> Value {
+    let fields = errors
+        .field_errors()
+        .into_iter()
+        .map(|(field, errors)| {
+            let messages = errors
+                .iter()
+                .map(|error| match error.message {
+                    Some(ref message) => message.to_string(),
+                    None => error.code.to_string(),
+                })
+                .collect::<Vec<String>>();
+            (field.to_string(), json!(messages))
+        })
+        .collect::<serde_json::Map<String, Value>>();
+
+    Value::Object(fields)
+}
+
+/// Extracts `username` from a detail message like `Key (username)=(ab) already exists.`
+fn key_column(detail: &str) -> Option<&str> {
+    let start = detail.strip_prefix("Key (")?;
+    start.find(')').map(|end| &start[..end])
+}
+
+impl ResponseError for MyError {
+    fn status_code(&self) -> StatusCode {
+        self.body().status
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let body = self.body();
+        HttpResponse::build(body.status).json(body)
+    }
+}
+
+/// Errors of the RFC 8628 device endpoints, rendered as RFC 6749 section 5.2
+/// `{"error": ..., "error_description": ...}` bodies instead of `ErrorBody`.
+#[derive(Debug, Display)]
+pub struct OAuthError(MyError);
+
+impl From<MyError> for OAuthError {
+    fn from(err: MyError) -> Self {
+        OAuthError(err)
+    }
+}
+
+impl ResponseError for OAuthError {
+    fn status_code(&self) -> StatusCode {
+        self.0.status_code()
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        let body = self.0.body();
+        let error = match body.code {
+            "bad_request" | "validation_failed" => "invalid_request",
+            "database_unavailable" => "temporarily_unavailable",
+            "internal_error" => "server_error",
+            code => code,
+        };
+        let description = match self.0 {
+            MyError::ValidationError(ref errors) => {
+                let fields: Vec<&str> = errors.field_errors().into_keys().collect();
+                format!("{}: {}", body.message, fields.join(", "))
+            }
+            _ => body.message,
+        };
+        HttpResponse::build(body.status)
+            .insert_header((header::CACHE_CONTROL, "no-store"))
+            .json(json!({ "error": error, "error_description": description }))
+    }
+}
@@ -0,0 +1,173 @@
+use actix_web::{get, post, web, Error, HttpResponse};
+use deadpool_postgres::{Client, Pool};
+
+use crate::{
+    auth::{
+        generate_refresh_token, generate_token_family, hash_refresh_token, require_parental_pin,
+        verify_password, AuthenticatedUser, JwtKeys, RequireAuth,
+    },
+    db,
+    errors::MyError,
+    models::{
+        auth::{LoginRequest, RefreshRequest, TokenResponse},
+        profiles::SelectProfileRequest,
+    },
+};
+
+fn token_response(
+    keys: &JwtKeys,
+    subject: AuthenticatedUser,
+    refresh_token: String,
+) -> Result<TokenResponse, MyError> {
+    Ok(TokenResponse {
+        access_token: keys.issue_access_token(subject)?,
+        token_type: "Bearer",
+        expires_in: keys.access_ttl_secs,
+        refresh_token,
+    })
+}
+
+/// Starts a new refresh token family for the subject, e.g. after a login.
+pub(super) async fn issue_tokens(
+    client: &Client,
+    keys: &JwtKeys,
+    subject: AuthenticatedUser,
+) -> Result<TokenResponse, MyError> {
+    let refresh_token = generate_refresh_token();
+    db::auth::add_refresh_token(
+        client,
+        subject,
+        &generate_token_family(),
+        &hash_refresh_token(&refresh_token),
+        keys.refresh_ttl_secs,
+    )
+    .await?;
+
+    token_response(keys, subject, refresh_token)
+}
+
+/// Exchanges a username and password for an access and a refresh token.
+#[utoipa::path(
+    tag = "auth",
+    request_body = LoginRequest,
+    responses(
+        (status = 200, description = "Tokens for the user", body = TokenResponse),
+        (status = 401, description = "Wrong username or password", body = ErrorBody),
+        (status = 404, description = "Unknown device", body = ErrorBody),
+    )
+)]
+#[post("/auth/login")]
+pub async fn login(
+    credentials: web::Json<LoginRequest>,
+    db_pool: web::Data<Pool>,
+    keys: web::Data<JwtKeys>,
+) -> Result<HttpResponse, Error> {
+    let LoginRequest {
+        username,
+        password,
+        device_id,
+    } = credentials.into_inner();
+
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let (user, password_hash) = match db::auth::get_credentials(&client, username.trim()).await? {
+        Some((user, password_hash)) => (Some(user), password_hash),
+        None => (None, None),
+    };
+
+    let valid = web::block(move || verify_password(&password, password_hash.as_deref())).await?;
+
+    let user = match user {
+        Some(user) if valid => user,
+        _ => return Err(MyError::InvalidCredentials.into()),
+    };
+
+    if let Some(device_id) = device_id {
+        if !db::device::touch_device(&client, device_id, user.id).await? {
+            return Err(MyError::NotFound.into());
+        }
+    }
+
+    let subject = AuthenticatedUser {
+        user_id: user.id,
+        device_id,
+        profile_id: None,
+    };
+
+    Ok(HttpResponse::Ok().json(issue_tokens(&client, &keys, subject).await?))
+}
+
+#[post("/auth/refresh")]
+pub async fn refresh(
+    request: web::Json<RefreshRequest>,
+    db_pool: web::Data<Pool>,
+    keys: web::Data<JwtKeys>,
+) -> Result<HttpResponse, Error> {
+    let mut client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let refresh_token = generate_refresh_token();
+    let subject = db::auth::rotate_refresh_token(
+        &mut client,
+        &hash_refresh_token(&request.refresh_token),
+        &hash_refresh_token(&refresh_token),
+        keys.refresh_ttl_secs,
+    )
+    .await?
+    .ok_or(MyError::InvalidRefreshToken)?;
+
+    if let Some(device_id) = subject.device_id {
+        db::device::touch_device(&client, device_id, subject.user_id).await?;
+    }
+
+    Ok(HttpResponse::Ok().json(token_response(&keys, subject, refresh_token)?))
+}
+
+#[post("/auth/logout")]
+pub async fn logout(
+    request: web::Json<RefreshRequest>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    db::auth::revoke_refresh_token_family(&client, &hash_refresh_token(&request.refresh_token))
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[get("/auth/me", wrap = "RequireAuth")]
+pub async fn me(auth: AuthenticatedUser, db_pool: web::Data<Pool>) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let user = db::get_user_by_id(&client, auth.user_id).await?;
+
+    Ok(HttpResponse::Ok().json(user))
+}
+
+/// Issues tokens scoped to one of the user's profiles, so history and
+/// preferences are kept per profile. Also used to switch profiles.
+#[post("/auth/profile", wrap = "RequireAuth")]
+pub async fn select_profile(
+    auth: AuthenticatedUser,
+    request: web::Json<SelectProfileRequest>,
+    db_pool: web::Data<Pool>,
+    keys: web::Data<JwtKeys>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let profile = db::profiles::get_profile(&client, request.profile_id, auth.user_id).await?;
+
+    // leaving a restricted profile for a less restricted one takes the parental PIN
+    if let Some(limit) = auth.age_rating_limit(&client).await? {
+        if profile.max_age_rating > limit {
+            require_parental_pin(&client, auth.user_id, request.pin.as_deref()).await?;
+        }
+    }
+
+    let subject = AuthenticatedUser {
+        profile_id: Some(profile.id),
+        ..auth
+    };
+
+    Ok(HttpResponse::Ok().json(issue_tokens(&client, &keys, subject).await?))
+}
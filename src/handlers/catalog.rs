@@ -0,0 +1,106 @@
+use actix_web::{get, web, Error, HttpResponse};
+use deadpool_postgres::{Client, Pool};
+
+use crate::{
+    auth::{AuthenticatedUser, OptionalAuth, Permission},
+    db,
+    errors::MyError,
+    models::catalog::{SearchQuery, TitleQuery},
+};
+
+#[get("/titles", wrap = "OptionalAuth")]
+pub async fn get_titles(
+    auth: Option<AuthenticatedUser>,
+    query: web::Query<TitleQuery>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let mut query = query.into_inner();
+    let limit = age_rating_limit(&client, auth).await?;
+    query.max_age_rating = match (query.max_age_rating, limit) {
+        (Some(requested), Some(limit)) => Some(requested.min(limit)),
+        (requested, limit) => requested.or(limit),
+    };
+    let titles = db::catalog::get_titles(&client, query).await?;
+
+    Ok(HttpResponse::Ok().json(titles))
+}
+
+#[get("/titles/{title_id}", wrap = "OptionalAuth")]
+pub async fn get_title(
+    auth: Option<AuthenticatedUser>,
+    path: web::Path<i64>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let limit = age_rating_limit(&client, auth).await?;
+    let title = db::catalog::get_title(&client, path.into_inner(), limit).await?;
+
+    Ok(HttpResponse::Ok().json(title))
+}
+
+#[get("/seasons/{season_id}", wrap = "OptionalAuth")]
+pub async fn get_season(
+    auth: Option<AuthenticatedUser>,
+    path: web::Path<i64>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let limit = age_rating_limit(&client, auth).await?;
+    let season = db::catalog::get_season(&client, path.into_inner(), limit).await?;
+
+    Ok(HttpResponse::Ok().json(season))
+}
+
+#[get("/episodes/{episode_id}", wrap = "OptionalAuth")]
+pub async fn get_episode(
+    auth: Option<AuthenticatedUser>,
+    path: web::Path<i64>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let limit = age_rating_limit(&client, auth).await?;
+    let episode = db::catalog::get_episode(&client, path.into_inner(), limit).await?;
+
+    Ok(HttpResponse::Ok().json(episode))
+}
+
+/// As-you-type search over title names and synopses, callers allowed to read
+/// users also get the matching users.
+#[get("/search", wrap = "OptionalAuth")]
+pub async fn search(
+    auth: Option<AuthenticatedUser>,
+    query: web::Query<SearchQuery>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let query = query.into_inner();
+    let users = match auth {
+        Some(auth) if auth.has_permission(&client, Permission::ReadUsers).await? => {
+            Some(db::search_users(&client, &query).await?)
+        }
+        _ => None,
+    };
+
+    let limit = age_rating_limit(&client, auth).await?;
+    let mut results = db::catalog::search_titles(&client, query, limit).await?;
+    results.users = users;
+
+    Ok(HttpResponse::Ok().json(results))
+}
+
+/// The catalog is public, a token for a profile hides the titles above its limit.
+async fn age_rating_limit(
+    client: &Client,
+    auth: Option<AuthenticatedUser>,
+) -> Result<Option<i16>, MyError> {
+    match auth {
+        Some(auth) => auth.age_rating_limit(client).await,
+        None => Ok(None),
+    }
+}
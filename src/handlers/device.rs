@@ -0,0 +1,207 @@
+use actix_web::{delete, get, http::header, post, web, Error, HttpResponse};
+use deadpool_postgres::{Client, Pool};
+use validator::Validate;
+
+use super::auth::issue_tokens;
+use crate::{
+    auth::{
+        format_user_code, generate_device_code, generate_user_code, hash_device_code,
+        normalize_user_code, require_parental_pin, AuthenticatedUser, JwtKeys, Permission,
+        RequireAuth,
+    },
+    config::DeviceConfig,
+    db,
+    errors::{MyError, OAuthError},
+    models::device::{
+        DeviceCodeRequest, DeviceCodeResponse, DevicePoll, DeviceTokenRequest, DeviceVerifyRequest,
+        NewDevice, DEVICE_CODE_GRANT_TYPE,
+    },
+};
+
+/// Approvals a user may attempt in a row without a matching code, user codes
+/// are short enough to be guessed otherwise.
+const MAX_USER_CODE_ATTEMPTS: i32 = 10;
+const USER_CODE_LOCK_SECS: f64 = 15.0 * 60.0;
+
+#[get("/users/{user_id}/devices", wrap = "RequireAuth")]
+pub async fn get_user_devices(
+    auth: AuthenticatedUser,
+    path: web::Path<i64>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let user_id = path.into_inner();
+    auth.require_self_or_permission(&client, user_id, Permission::ReadUsers)
+        .await?;
+
+    // 404 for unknown users rather than an empty list
+    db::get_user_by_id(&client, user_id).await?;
+    let devices = db::device::get_user_devices(&client, user_id).await?;
+
+    Ok(HttpResponse::Ok().json(devices))
+}
+
+#[post("/users/{user_id}/devices", wrap = "RequireAuth")]
+pub async fn add_device(
+    auth: AuthenticatedUser,
+    path: web::Path<i64>,
+    device: web::Json<NewDevice>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let user_id = path.into_inner();
+    let device = device.into_inner();
+    device.validate().map_err(MyError::ValidationError)?;
+
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+    auth.require_self_or_permission(&client, user_id, Permission::WriteUsers)
+        .await?;
+
+    db::get_user_by_id(&client, user_id).await?;
+    let device = db::device::add_device(&client, user_id, &device).await?;
+
+    Ok(HttpResponse::Ok().json(device))
+}
+
+/// Signs the device out, its refresh tokens are deleted and its access tokens
+/// are rejected from now on.
+#[delete("/users/{user_id}/devices/{device_id}", wrap = "RequireAuth")]
+pub async fn delete_device(
+    auth: AuthenticatedUser,
+    path: web::Path<(i64, i64)>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let (user_id, device_id) = path.into_inner();
+    auth.require_self_or_permission(&client, user_id, Permission::WriteUsers)
+        .await?;
+
+    db::device::delete_device(&client, device_id, user_id).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// RFC 8628 device authorization request, called by the TV to start pairing;
+/// the user code is then shown on screen. Device details are optional, when
+/// sent the TV is registered to the user approving the code.
+#[post("/device/code")]
+pub async fn device_code(
+    request: Result<web::Form<DeviceCodeRequest>, Error>,
+    db_pool: web::Data<Pool>,
+    config: web::Data<DeviceConfig>,
+) -> Result<HttpResponse, OAuthError> {
+    let device = form(request)?.device()?;
+
+    let device_code = generate_device_code();
+    let user_code = generate_user_code();
+
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    db::device::add_device_code(
+        &client,
+        &hash_device_code(&device_code),
+        &user_code,
+        config.interval_secs,
+        config.code_ttl_secs,
+        device.as_ref(),
+    )
+    .await?;
+
+    let user_code = format_user_code(&user_code);
+    let separator = if config.verification_uri.contains('?') {
+        '&'
+    } else {
+        '?'
+    };
+    Ok(HttpResponse::Ok().json(DeviceCodeResponse {
+        device_code,
+        verification_uri_complete: format!(
+            "{}{}user_code={}",
+            config.verification_uri, separator, user_code
+        ),
+        user_code,
+        verification_uri: config.verification_uri.clone(),
+        expires_in: config.code_ttl_secs,
+        interval: config.interval_secs,
+    }))
+}
+
+/// Called by the signed in user with the code shown on the TV, see
+/// `MAX_USER_CODE_ATTEMPTS`.
+#[post("/device/verify", wrap = "RequireAuth")]
+pub async fn verify_device(
+    auth: AuthenticatedUser,
+    request: web::Json<DeviceVerifyRequest>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    // the TV gets a token for the whole account, which a profile cannot hand out
+    // without the parental PIN
+    if request.approve && auth.age_rating_limit(&client).await?.is_some() {
+        require_parental_pin(&client, auth.user_id, request.pin.as_deref()).await?;
+    }
+
+    db::device::approve_device_code(
+        &client,
+        &normalize_user_code(&request.user_code),
+        auth.user_id,
+        request.approve,
+        MAX_USER_CODE_ATTEMPTS,
+        USER_CODE_LOCK_SECS,
+    )
+    .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// RFC 8628 access token request, polled by the TV until the user approves or
+/// denies the code.
+#[post("/device/token")]
+pub async fn device_token(
+    request: Result<web::Form<DeviceTokenRequest>, Error>,
+    db_pool: web::Data<Pool>,
+    keys: web::Data<JwtKeys>,
+) -> Result<HttpResponse, OAuthError> {
+    let request = form(request)?;
+    if request.grant_type != DEVICE_CODE_GRANT_TYPE {
+        return Err(MyError::UnsupportedGrantType.into());
+    }
+
+    let mut client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let poll = db::device::poll_device_code(&mut client, &hash_device_code(&request.device_code))
+        .await?
+        .ok_or(MyError::InvalidDeviceCode)?;
+
+    let (user_id, device) = match poll {
+        DevicePoll::Approved { user_id, device } => (user_id, device),
+        DevicePoll::Pending => return Err(MyError::AuthorizationPending.into()),
+        DevicePoll::SlowDown => return Err(MyError::SlowDown.into()),
+        DevicePoll::Denied => return Err(MyError::AccessDenied.into()),
+        DevicePoll::Expired => return Err(MyError::ExpiredToken.into()),
+    };
+
+    let device_id = match device {
+        Some(device) => Some(db::device::add_device(&client, user_id, &device).await?.id),
+        None => None,
+    };
+
+    let subject = AuthenticatedUser {
+        user_id,
+        device_id,
+        profile_id: None,
+    };
+
+    Ok(HttpResponse::Ok()
+        .insert_header((header::CACHE_CONTROL, "no-store"))
+        .json(issue_tokens(&client, &keys, subject).await?))
+}
+
+/// Unreadable forms are an `invalid_request` like any other malformed request.
+fn form<T>(request: Result<web::Form<T>, Error>) -> Result<T, MyError> {
+    request
+        .map(web::Form::into_inner)
+        .map_err(|err| MyError::BadRequest(err.to_string()))
+}
@@ -0,0 +1,16 @@
+use actix_web::{get, web, Error, HttpResponse};
+use deadpool_postgres::{Client, Pool};
+
+use crate::{db, errors::MyError, models::epg::EpgQuery};
+
+#[get("/epg")]
+pub async fn get_epg(
+    query: web::Query<EpgQuery>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let schedule = db::epg::get_schedule(&client, query.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(schedule))
+}
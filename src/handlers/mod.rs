@@ -1 +1,262 @@
+use actix_web::{delete, get, patch, put, web, Error, HttpResponse};
+use deadpool_postgres::{Client, Pool};
+use validator::Validate;
 
+pub mod auth;
+pub mod catalog;
+pub mod device;
+pub mod epg;
+pub mod openapi;
+pub mod parental;
+pub mod profiles;
+pub mod progress;
+pub mod roles;
+pub mod watchlist;
+
+use crate::{
+    auth::{hash_password, require_current_password, AuthenticatedUser, Permission, RequireAuth},
+    db,
+    errors::MyError,
+    models::{NewUser, ReplaceUser, UpdateUser, UserQuery},
+};
+
+/// Registers a new user.
+#[utoipa::path(
+    post,
+    path = "/users",
+    tag = "users",
+    request_body = NewUser,
+    responses(
+        (status = 200, description = "The new user", body = User),
+        (status = 409, description = "Username or email is taken", body = ErrorBody),
+        (status = 422, description = "Invalid user", body = ErrorBody),
+    )
+)]
+pub async fn add_user(
+    user: web::Json<NewUser>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let user_info: NewUser = user.into_inner().normalize();
+    user_info.validate().map_err(MyError::ValidationError)?;
+
+    let password = user_info.password.clone();
+    let password_hash = web::block(move || hash_password(&password)).await??;
+
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let new_user = db::add_user(&client, user_info, password_hash).await?;
+
+    Ok(HttpResponse::Ok().json(new_user))
+}
+
+/// Lists users, requires the `users:read` permission.
+#[utoipa::path(
+    get,
+    path = "/users",
+    tag = "users",
+    params(UserQuery),
+    responses(
+        (status = 200, description = "A page of users", body = UserPage),
+        (status = 400, description = "Invalid query", body = ErrorBody),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Missing permission", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
+pub async fn get_users(
+    auth: AuthenticatedUser,
+    query: web::Query<UserQuery>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    auth.require_permission(&client, Permission::ReadUsers)
+        .await?;
+
+    let users = db::get_users(&client, query.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(users))
+}
+
+/// Gets a user, requires the `users:read` permission for other users.
+#[utoipa::path(
+    tag = "users",
+    params(("user_id" = i64, Path, description = "Id of the user")),
+    responses(
+        (status = 200, description = "The user", body = User),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Not the user and missing permission", body = ErrorBody),
+        (status = 404, description = "Unknown user", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
+#[get("/users/{user_id}", wrap = "RequireAuth")]
+pub async fn get_user_by_id(
+    auth: AuthenticatedUser,
+    path: web::Path<i64>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let user_id = path.into_inner();
+    auth.require_self_or_permission(&client, user_id, Permission::ReadUsers)
+        .await?;
+
+    let user = db::get_user_by_id(&client, user_id).await?;
+
+    Ok(HttpResponse::Ok().json(user))
+}
+
+/// Replaces a user, requires the `users:write` permission for other users.
+/// Signs the user out of every session since the password is replaced too.
+#[utoipa::path(
+    tag = "users",
+    params(("user_id" = i64, Path, description = "Id of the user")),
+    request_body = ReplaceUser,
+    responses(
+        (status = 200, description = "The updated user", body = User),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Not permitted or wrong current password", body = ErrorBody),
+        (status = 404, description = "Unknown user", body = ErrorBody),
+        (status = 409, description = "Username or email is taken", body = ErrorBody),
+        (status = 422, description = "Invalid user", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
+#[put("/users/{user_id}", wrap = "RequireAuth")]
+pub async fn replace_user(
+    auth: AuthenticatedUser,
+    path: web::Path<i64>,
+    user: web::Json<ReplaceUser>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let user_id = path.into_inner();
+    let ReplaceUser {
+        user,
+        current_password,
+    } = user.into_inner();
+    let user_info = user.normalize();
+    user_info.validate().map_err(MyError::ValidationError)?;
+
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+    auth.require_self_or_permission(&client, user_id, Permission::WriteUsers)
+        .await?;
+
+    let password_hash = hash_new_password(
+        &client,
+        auth,
+        user_id,
+        user_info.password.clone(),
+        current_password.as_deref(),
+    )
+    .await?;
+
+    let user = db::update_user(
+        &client,
+        user_id,
+        UpdateUser::from(user_info),
+        Some(password_hash),
+    )
+    .await?;
+    db::auth::revoke_user_refresh_tokens(&client, user_id).await?;
+
+    Ok(HttpResponse::Ok().json(user))
+}
+
+/// Updates the given fields of a user, requires the `users:write` permission for other users.
+/// Changing the password signs the user out of every session.
+#[utoipa::path(
+    tag = "users",
+    params(("user_id" = i64, Path, description = "Id of the user")),
+    request_body = UpdateUser,
+    responses(
+        (status = 200, description = "The updated user", body = User),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Not permitted or wrong current password", body = ErrorBody),
+        (status = 404, description = "Unknown user", body = ErrorBody),
+        (status = 409, description = "Username or email is taken", body = ErrorBody),
+        (status = 422, description = "Invalid changes", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
+#[patch("/users/{user_id}", wrap = "RequireAuth")]
+pub async fn update_user(
+    auth: AuthenticatedUser,
+    path: web::Path<i64>,
+    user: web::Json<UpdateUser>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let user_id = path.into_inner();
+    let user_info = user.into_inner().normalize();
+    user_info.validate().map_err(MyError::ValidationError)?;
+
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+    auth.require_self_or_permission(&client, user_id, Permission::WriteUsers)
+        .await?;
+
+    let password_hash = match user_info.password.clone() {
+        Some(password) => Some(
+            hash_new_password(
+                &client,
+                auth,
+                user_id,
+                password,
+                user_info.current_password.as_deref(),
+            )
+            .await?,
+        ),
+        None => None,
+    };
+    let password_changed = password_hash.is_some();
+
+    let user = db::update_user(&client, user_id, user_info, password_hash).await?;
+    if password_changed {
+        db::auth::revoke_user_refresh_tokens(&client, user_id).await?;
+    }
+
+    Ok(HttpResponse::Ok().json(user))
+}
+
+/// Deletes a user, requires the `users:write` permission for other users.
+#[utoipa::path(
+    tag = "users",
+    params(("user_id" = i64, Path, description = "Id of the user")),
+    responses(
+        (status = 204, description = "The user was deleted"),
+        (status = 401, description = "Missing or invalid token", body = ErrorBody),
+        (status = 403, description = "Not the user and missing permission", body = ErrorBody),
+        (status = 404, description = "Unknown user", body = ErrorBody),
+    ),
+    security(("bearer_auth" = []))
+)]
+#[delete("/users/{user_id}", wrap = "RequireAuth")]
+pub async fn delete_user(
+    auth: AuthenticatedUser,
+    path: web::Path<i64>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let user_id = path.into_inner();
+    auth.require_self_or_permission(&client, user_id, Permission::WriteUsers)
+        .await?;
+
+    db::delete_user(&client, user_id).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Hashes a new password, users changing their own have to confirm the current one.
+async fn hash_new_password(
+    client: &Client,
+    auth: AuthenticatedUser,
+    user_id: i64,
+    password: String,
+    current_password: Option<&str>,
+) -> Result<String, Error> {
+    if auth.user_id == user_id {
+        require_current_password(client, user_id, current_password).await?;
+    }
+
+    Ok(web::block(move || hash_password(&password)).await??)
+}
@@ -0,0 +1,55 @@
+use actix_web::{post, put, web, Error, HttpResponse};
+use deadpool_postgres::{Client, Pool};
+use validator::Validate;
+
+use crate::{
+    auth::{
+        hash_password, require_parental_pin, verify_parental_pin, AuthenticatedUser, Permission,
+        RequireAuth,
+    },
+    db,
+    errors::MyError,
+    models::parental::{SetPinRequest, VerifyPinRequest},
+};
+
+/// Sets the parental PIN, changing an existing PIN takes the current one.
+#[put("/users/{user_id}/parental-pin", wrap = "RequireAuth")]
+pub async fn set_pin(
+    auth: AuthenticatedUser,
+    path: web::Path<i64>,
+    request: web::Json<SetPinRequest>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let user_id = path.into_inner();
+    let request = request.into_inner();
+    request.validate().map_err(MyError::ValidationError)?;
+
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+    auth.require_self_or_permission(&client, user_id, Permission::WriteUsers)
+        .await?;
+    require_parental_pin(&client, user_id, request.current_pin.as_deref()).await?;
+
+    let pin = request.pin;
+    let pin_hash = web::block(move || hash_password(&pin)).await??;
+    db::parental::set_parental_pin(&client, user_id, &pin_hash).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+/// Lets clients gate actions of their own, e.g. playing a title, behind the PIN.
+#[post("/users/{user_id}/parental-pin/verify", wrap = "RequireAuth")]
+pub async fn verify_pin(
+    auth: AuthenticatedUser,
+    path: web::Path<i64>,
+    request: web::Json<VerifyPinRequest>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let user_id = path.into_inner();
+
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+    auth.require_self_or_permission(&client, user_id, Permission::WriteUsers)
+        .await?;
+    verify_parental_pin(&client, user_id, &request.pin).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
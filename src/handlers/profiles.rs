@@ -0,0 +1,116 @@
+use actix_web::{delete, get, patch, post, web, Error, HttpResponse};
+use deadpool_postgres::{Client, Pool};
+use validator::Validate;
+
+use crate::{
+    auth::{require_parental_pin, AuthenticatedUser, Permission, RequireAuth},
+    db,
+    errors::MyError,
+    models::profiles::{NewProfile, UpdateProfile},
+};
+
+#[get("/users/{user_id}/profiles", wrap = "RequireAuth")]
+pub async fn get_user_profiles(
+    auth: AuthenticatedUser,
+    path: web::Path<i64>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let user_id = path.into_inner();
+    auth.require_self_or_permission(&client, user_id, Permission::ReadUsers)
+        .await?;
+
+    // 404 for unknown users rather than an empty list
+    db::get_user_by_id(&client, user_id).await?;
+    let profiles = db::profiles::get_user_profiles(&client, user_id).await?;
+
+    Ok(HttpResponse::Ok().json(profiles))
+}
+
+#[post("/users/{user_id}/profiles", wrap = "RequireAuth")]
+pub async fn add_profile(
+    auth: AuthenticatedUser,
+    path: web::Path<i64>,
+    profile: web::Json<NewProfile>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let user_id = path.into_inner();
+    let profile = profile.into_inner().normalize();
+    profile.validate().map_err(MyError::ValidationError)?;
+
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+    auth.require_self_or_permission(&client, user_id, Permission::WriteUsers)
+        .await?;
+
+    db::get_user_by_id(&client, user_id).await?;
+
+    // like switching profiles, a restricted profile cannot create its way out
+    if let Some(limit) = auth.age_rating_limit(&client).await? {
+        if profile.max_age_rating.is_some_and(|rating| rating > limit) {
+            require_parental_pin(&client, user_id, profile.pin.as_deref()).await?;
+        }
+    }
+
+    let profile = db::profiles::add_profile(&client, user_id, profile).await?;
+
+    Ok(HttpResponse::Ok().json(profile))
+}
+
+#[get("/users/{user_id}/profiles/{profile_id}", wrap = "RequireAuth")]
+pub async fn get_profile(
+    auth: AuthenticatedUser,
+    path: web::Path<(i64, i64)>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let (user_id, profile_id) = path.into_inner();
+    auth.require_self_or_permission(&client, user_id, Permission::ReadUsers)
+        .await?;
+
+    let profile = db::profiles::get_profile(&client, profile_id, user_id).await?;
+
+    Ok(HttpResponse::Ok().json(profile))
+}
+
+#[patch("/users/{user_id}/profiles/{profile_id}", wrap = "RequireAuth")]
+pub async fn update_profile(
+    auth: AuthenticatedUser,
+    path: web::Path<(i64, i64)>,
+    profile: web::Json<UpdateProfile>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let (user_id, profile_id) = path.into_inner();
+    let profile = profile.into_inner().normalize();
+    profile.validate().map_err(MyError::ValidationError)?;
+
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+    auth.require_self_or_permission(&client, user_id, Permission::WriteUsers)
+        .await?;
+    if profile.changes_restrictions() {
+        require_parental_pin(&client, user_id, profile.pin.as_deref()).await?;
+    }
+
+    let profile = db::profiles::update_profile(&client, profile_id, user_id, profile).await?;
+
+    Ok(HttpResponse::Ok().json(profile))
+}
+
+/// Also signs out the sessions that selected the profile.
+#[delete("/users/{user_id}/profiles/{profile_id}", wrap = "RequireAuth")]
+pub async fn delete_profile(
+    auth: AuthenticatedUser,
+    path: web::Path<(i64, i64)>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let (user_id, profile_id) = path.into_inner();
+    auth.require_self_or_permission(&client, user_id, Permission::WriteUsers)
+        .await?;
+
+    db::profiles::delete_profile(&client, profile_id, user_id).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,52 @@
+use actix_web::{get, post, web, Error, HttpResponse};
+use deadpool_postgres::{Client, Pool};
+use validator::Validate;
+
+use crate::{
+    auth::{AuthenticatedUser, RequireAuth},
+    db,
+    errors::MyError,
+    models::progress::{ContinueWatchingQuery, NewProgress},
+};
+
+#[post("/profiles/{profile_id}/progress", wrap = "RequireAuth")]
+pub async fn save_progress(
+    auth: AuthenticatedUser,
+    path: web::Path<i64>,
+    progress: web::Json<NewProgress>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let profile_id = path.into_inner();
+    auth.require_profile_scope(profile_id)?;
+
+    progress.validate().map_err(MyError::ValidationError)?;
+    if progress.title_id.is_some() == progress.episode_id.is_some() {
+        return Err(MyError::BadRequest(
+            "exactly one of title_id and episode_id is required".into(),
+        )
+        .into());
+    }
+
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    // ownership of the profile is checked by the upsert itself
+    db::progress::save_progress(&client, profile_id, auth.user_id, &progress).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[get("/profiles/{profile_id}/continue-watching", wrap = "RequireAuth")]
+pub async fn get_continue_watching(
+    auth: AuthenticatedUser,
+    path: web::Path<i64>,
+    query: web::Query<ContinueWatchingQuery>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let profile = auth.require_profile(&client, path.into_inner()).await?;
+
+    let items = db::progress::get_continue_watching(&client, profile.id, query.limit).await?;
+
+    Ok(HttpResponse::Ok().json(items))
+}
@@ -0,0 +1,72 @@
+use actix_web::{delete, get, put, web, Error, HttpResponse};
+use deadpool_postgres::{Client, Pool};
+
+use crate::{
+    auth::{AuthenticatedUser, Permission, RequireAuth},
+    db,
+    errors::MyError,
+};
+
+#[get("/roles", wrap = "RequireAuth")]
+pub async fn get_roles(db_pool: web::Data<Pool>) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let roles = db::roles::get_roles(&client).await?;
+
+    Ok(HttpResponse::Ok().json(roles))
+}
+
+#[get("/users/{user_id}/roles", wrap = "RequireAuth")]
+pub async fn get_user_roles(
+    auth: AuthenticatedUser,
+    path: web::Path<i64>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let user_id = path.into_inner();
+    auth.require_self_or_permission(&client, user_id, Permission::ReadUsers)
+        .await?;
+
+    // 404 for unknown users rather than an empty list
+    db::get_user_by_id(&client, user_id).await?;
+    let roles = db::roles::get_user_roles(&client, user_id).await?;
+
+    Ok(HttpResponse::Ok().json(roles))
+}
+
+#[put("/users/{user_id}/roles/{role}", wrap = "RequireAuth")]
+pub async fn add_user_role(
+    auth: AuthenticatedUser,
+    path: web::Path<(i64, String)>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    auth.require_permission(&client, Permission::ManageRoles)
+        .await?;
+
+    let (user_id, role) = path.into_inner();
+
+    db::roles::add_user_role(&client, user_id, &role).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[delete("/users/{user_id}/roles/{role}", wrap = "RequireAuth")]
+pub async fn delete_user_role(
+    auth: AuthenticatedUser,
+    path: web::Path<(i64, String)>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    auth.require_permission(&client, Permission::ManageRoles)
+        .await?;
+
+    let (user_id, role) = path.into_inner();
+
+    db::roles::delete_user_role(&client, user_id, &role).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
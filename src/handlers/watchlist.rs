@@ -0,0 +1,78 @@
+use actix_web::{delete, get, patch, post, web, Error, HttpResponse};
+use deadpool_postgres::{Client, Pool};
+use validator::Validate;
+
+use crate::{
+    auth::{AuthenticatedUser, RequireAuth},
+    db,
+    errors::MyError,
+    models::watchlist::{MoveWatchlistEntry, NewWatchlistEntry, WatchlistQuery},
+};
+
+#[get("/profiles/{profile_id}/watchlist", wrap = "RequireAuth")]
+pub async fn get_watchlist(
+    auth: AuthenticatedUser,
+    path: web::Path<i64>,
+    query: web::Query<WatchlistQuery>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let profile = auth.require_profile(&client, path.into_inner()).await?;
+
+    let watchlist = db::watchlist::get_watchlist(&client, profile.id, query.into_inner()).await?;
+
+    Ok(HttpResponse::Ok().json(watchlist))
+}
+
+#[post("/profiles/{profile_id}/watchlist", wrap = "RequireAuth")]
+pub async fn add_watchlist_entry(
+    auth: AuthenticatedUser,
+    path: web::Path<i64>,
+    entry: web::Json<NewWatchlistEntry>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let mut client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let profile = auth.require_profile(&client, path.into_inner()).await?;
+
+    let entry = db::watchlist::add_watchlist_entry(&mut client, profile.id, entry.title_id).await?;
+
+    Ok(HttpResponse::Ok().json(entry))
+}
+
+#[patch("/profiles/{profile_id}/watchlist/{title_id}", wrap = "RequireAuth")]
+pub async fn move_watchlist_entry(
+    auth: AuthenticatedUser,
+    path: web::Path<(i64, i64)>,
+    request: web::Json<MoveWatchlistEntry>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    request.validate().map_err(MyError::ValidationError)?;
+
+    let mut client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let (profile_id, title_id) = path.into_inner();
+    let profile = auth.require_profile(&client, profile_id).await?;
+
+    db::watchlist::move_watchlist_entry(&mut client, profile.id, title_id, request.position)
+        .await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
+
+#[delete("/profiles/{profile_id}/watchlist/{title_id}", wrap = "RequireAuth")]
+pub async fn delete_watchlist_entry(
+    auth: AuthenticatedUser,
+    path: web::Path<(i64, i64)>,
+    db_pool: web::Data<Pool>,
+) -> Result<HttpResponse, Error> {
+    let mut client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
+
+    let (profile_id, title_id) = path.into_inner();
+    let profile = auth.require_profile(&client, profile_id).await?;
+
+    db::watchlist::delete_watchlist_entry(&mut client, profile.id, title_id).await?;
+
+    Ok(HttpResponse::NoContent().finish())
+}
@@ -0,0 +1,24 @@
+//! `tracing` setup: a `request` span per HTTP request and a debug span per
+//! `db` call, each logged when it closes together with its timings.
+
+mod middleware;
+
+use tracing_subscriber::{fmt::format::FmtSpan, EnvFilter};
+
+pub use middleware::RequestLogger;
+
+use crate::config::{LogConfig, LogFormat};
+
+pub fn init(config: &LogConfig) -> Result<(), String> {
+    let filter = EnvFilter::try_new(&config.level)
+        .map_err(|err| format!("invalid LOG.LEVEL '{}': {}", config.level, err))?;
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(FmtSpan::CLOSE);
+
+    match config.format {
+        LogFormat::Pretty => builder.pretty().try_init(),
+        LogFormat::Json => builder.json().try_init(),
+    }
+    .map_err(|err| err.to_string())
+}
@@ -1,174 +1,206 @@
-mod config {
-    use serde::Deserialize;
-    #[derive(Debug, Default, Deserialize)]
-    pub struct ExampleConfig {
-        pub server_addr: String,
-        pub pg: deadpool_postgres::Config,
-    }
+mod auth;
+mod config;
+mod db;
+mod errors;
+mod handlers;
+mod logging;
+mod migrations;
+mod models;
+mod openapi;
+mod tls;
+mod xmltv;
+
+use std::io::{Error as IoError, ErrorKind};
+
+use ::config::Config;
+use actix_web::{web, App, HttpResponse, HttpServer};
+use deadpool_postgres::Pool;
+use dotenv::dotenv;
+use handlers::*;
+
+use crate::{auth::JwtKeys, config::ExampleConfig, errors::MyError};
+
+async fn handle_echo() -> HttpResponse {
+    HttpResponse::Ok().body("Server working")
 }
 
-mod models {
-    use serde::{Deserialize, Serialize};
-    use tokio_pg_mapper_derive::PostgresMapper;
-
-    #[derive(Deserialize, PostgresMapper, Serialize)]
-    #[pg_mapper(table = "users")] // singular 'user' is a keyword..
-    pub struct User {
-        pub email: String,
-        pub first_name: String,
-        pub last_name: String,
-        pub username: String,
-    }
+fn io_error(err: MyError) -> IoError {
+    IoError::other(err)
 }
 
-mod errors {
-    use actix_web::{HttpResponse, ResponseError};
-    use deadpool_postgres::PoolError;
-    use derive_more::{Display, From};
-    use tokio_pg_mapper::Error as PGMError;
-    use tokio_postgres::error::Error as PGError;
-
-    #[derive(Display, From, Debug)]
-    pub enum MyError {
-        NotFound,
-        PGError(PGError),
-        PGMError(PGMError),
-        PoolError(PoolError),
-    }
-    impl std::error::Error for MyError {}
-
-    impl ResponseError for MyError {
-        fn error_response(&self) -> HttpResponse {
-            match *self {
-                MyError::NotFound => HttpResponse::NotFound().finish(),
-                MyError::PoolError(ref err) => {
-                    HttpResponse::InternalServerError().body(err.to_string())
+async fn migrate(pool: &Pool, schema: &str, command: &str) -> std::io::Result<()> {
+    let mut client = pool.get().await.map_err(|err| io_error(err.into()))?;
+
+    match command {
+        "list" => {
+            for migration in migrations::status(&client, schema)
+                .await
+                .map_err(io_error)?
+            {
+                println!(
+                    "{:04} {:<32} {}",
+                    migration.version,
+                    migration.name,
+                    migration.applied_at.as_deref().unwrap_or("pending")
+                );
+            }
+        }
+        "apply" => {
+            let applied = migrations::apply(&mut client, schema)
+                .await
+                .map_err(io_error)?;
+            println!("Applied {} migration(s)", applied.len());
+        }
+        "verify" => {
+            let mut problems = migrations::verify(&client, schema)
+                .await
+                .map_err(io_error)?;
+            problems.extend(
+                migrations::status(&client, schema)
+                    .await
+                    .map_err(io_error)?
+                    .iter()
+                    .filter(|migration| migration.applied_at.is_none())
+                    .map(|migration| {
+                        format!(
+                            "migration {} ({}) is pending",
+                            migration.version, migration.name
+                        )
+                    }),
+            );
+            if !problems.is_empty() {
+                for problem in &problems {
+                    eprintln!("{}", problem);
                 }
-                _ => HttpResponse::InternalServerError().finish(),
+                return Err(IoError::other("migration verification failed"));
             }
+            println!("Migrations OK");
         }
-    }
-}
-
-mod db {
-    use deadpool_postgres::Client;
-    use tokio_pg_mapper::FromTokioPostgresRow;
-
-    use crate::{errors::MyError, models::User};
-
-    pub async fn add_user(client: &Client, user_info: User) -> Result<User, MyError> {
-        let _stmt = include_str!("../sql/add_user.sql");
-        let _stmt = _stmt.replace("$table_fields", &User::sql_table_fields());
-        let stmt = client.prepare(&_stmt).await.unwrap();
-
-        client
-            .query(
-                &stmt,
-                &[
-                    &user_info.email,
-                    &user_info.first_name,
-                    &user_info.last_name,
-                    &user_info.username,
-                ],
-            )
-            .await?
-            .iter()
-            .map(|row| User::from_row_ref(row).unwrap())
-            .collect::<Vec<User>>()
-            .pop()
-            .ok_or(MyError::NotFound) // more applicable for SELECTs
-    }
-
-    pub async fn get_users(client: &Client) -> Result<Vec<User>, MyError> {
-        let _stmt = include_str!("../sql/get_users.sql");
-        let _stmt = _stmt.replace("$table_fields", &User::sql_table_fields());
-        let stmt = client
-            .prepare(&_stmt)
-            .await
-            .expect("failed to prepare sql statement");
-
-        match client.query(&stmt, &[]).await {
-            Ok(rows) => Ok(rows
-                .iter()
-                .map(|row| User::from_row_ref(row).unwrap())
-                .collect::<Vec<User>>()),
-            Err(e) => Err(MyError::PGError(e)),
+        _ => {
+            return Err(IoError::new(
+                ErrorKind::InvalidInput,
+                "usage: vidaa-server migrate <list|apply|verify>",
+            ))
         }
     }
 
-    pub async fn get_user_by_id(client: &Client, user_id: u32) -> Result<User, MyError> {
-        let _stmt = "SELECT $table_fields FROM testing.users WHERE id = $1";
-        let _stmt = _stmt.replace("$table_fields", &User::sql_table_fields());
-        let stmt = client
-            .prepare(&_stmt)
-            .await
-            .expect("failed to prepare sql statement");
-
-        client
-            .query(&stmt, &[&user_id])
-            .await?
-            .iter()
-            .map(|row| User::from_row_ref(row).unwrap())
-            .collect::<Vec<User>>()
-            .pop()
-            .ok_or(MyError::NotFound) // more applicable for SELECTs
-    }
+    Ok(())
 }
 
-mod handlers {
-    use actix_web::{get, web, Error, HttpResponse};
-    use deadpool_postgres::{Client, Pool};
+async fn import_xmltv(pool: &Pool, path: &str) -> std::io::Result<()> {
+    let guide = xmltv::read_file(path).map_err(|err| IoError::new(ErrorKind::InvalidData, err))?;
+    let mut client = pool.get().await.map_err(|err| io_error(err.into()))?;
 
-    use crate::{db, errors::MyError, models::User};
+    let summary = db::epg::import_guide(&mut client, &guide.channels, &guide.programs)
+        .await
+        .map_err(io_error)?;
+    println!(
+        "Imported {} channel(s) and {} program(s), skipped {} invalid program(s) and {} without a channel",
+        summary.channels, summary.programs, guide.invalid, summary.skipped
+    );
 
-    pub async fn add_user(
-        user: web::Json<User>,
-        db_pool: web::Data<Pool>,
-    ) -> Result<HttpResponse, Error> {
-        let user_info: User = user.into_inner();
-
-        let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
-
-        let new_user = db::add_user(&client, user_info)
-            .await
-            .expect("could not create user");
-
-        Ok(HttpResponse::Ok().json(new_user))
-    }
-
-    pub async fn get_users(db_pool: web::Data<Pool>) -> Result<HttpResponse, Error> {
-        let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
-
-        let users = db::get_users(&client).await?;
-
-        Ok(HttpResponse::Ok().json(users))
-    }
-
-    #[get("/users/{user_id}")]
-    pub async fn get_user_by_id(
-        path: web::Path<u32>,
-        db_pool: web::Data<Pool>,
-    ) -> Result<HttpResponse, Error> {
-        let client: Client = db_pool.get().await.map_err(MyError::PoolError)?;
-
-        let user_id = path.into_inner();
-
-        let user = db::get_user_by_id(&client, user_id).await?;
-
-        Ok(HttpResponse::Ok().json(user))
-    }
+    Ok(())
 }
 
-use ::config::Config;
-use actix_web::{web, App, HttpResponse, HttpServer};
-use dotenv::dotenv;
-use handlers::*;
-use tokio_postgres::NoTls;
-
-use crate::config::ExampleConfig;
-
-async fn handle_echo() -> HttpResponse {
-    HttpResponse::Ok().body("Server working")
+/// Every method and path registered by `routes`, update both together. The
+/// OpenAPI test checks the list against the app and against the spec.
+#[cfg(test)]
+const ROUTES: &[(&str, &str)] = &[
+    ("POST", "/users"),
+    ("GET", "/users"),
+    ("GET", "/users/{user_id}"),
+    ("PUT", "/users/{user_id}"),
+    ("PATCH", "/users/{user_id}"),
+    ("DELETE", "/users/{user_id}"),
+    ("POST", "/auth/login"),
+    ("POST", "/auth/refresh"),
+    ("POST", "/auth/logout"),
+    ("GET", "/auth/me"),
+    ("POST", "/auth/profile"),
+    ("GET", "/titles"),
+    ("GET", "/titles/{title_id}"),
+    ("GET", "/seasons/{season_id}"),
+    ("GET", "/episodes/{episode_id}"),
+    ("GET", "/search"),
+    ("GET", "/users/{user_id}/devices"),
+    ("POST", "/users/{user_id}/devices"),
+    ("DELETE", "/users/{user_id}/devices/{device_id}"),
+    ("POST", "/device/code"),
+    ("POST", "/device/verify"),
+    ("POST", "/device/token"),
+    ("GET", "/epg"),
+    ("GET", "/openapi.json"),
+    ("GET", "/docs"),
+    ("GET", "/docs/{_:.*}"),
+    ("PUT", "/users/{user_id}/parental-pin"),
+    ("POST", "/users/{user_id}/parental-pin/verify"),
+    ("GET", "/users/{user_id}/profiles"),
+    ("POST", "/users/{user_id}/profiles"),
+    ("GET", "/users/{user_id}/profiles/{profile_id}"),
+    ("PATCH", "/users/{user_id}/profiles/{profile_id}"),
+    ("DELETE", "/users/{user_id}/profiles/{profile_id}"),
+    ("POST", "/profiles/{profile_id}/progress"),
+    ("GET", "/profiles/{profile_id}/continue-watching"),
+    ("GET", "/roles"),
+    ("GET", "/users/{user_id}/roles"),
+    ("PUT", "/users/{user_id}/roles/{role}"),
+    ("DELETE", "/users/{user_id}/roles/{role}"),
+    ("GET", "/profiles/{profile_id}/watchlist"),
+    ("POST", "/profiles/{profile_id}/watchlist"),
+    ("PATCH", "/profiles/{profile_id}/watchlist/{title_id}"),
+    ("DELETE", "/profiles/{profile_id}/watchlist/{title_id}"),
+    ("GET", "/"),
+];
+
+/// Registers every route, shared with the OpenAPI spec test.
+fn routes(cfg: &mut web::ServiceConfig) {
+    cfg.service(
+        web::resource("/users")
+            .route(web::post().to(add_user))
+            .route(web::get().to(get_users).wrap(auth::RequireAuth)),
+    )
+    .service(get_user_by_id)
+    .service(replace_user)
+    .service(update_user)
+    .service(delete_user)
+    .service(handlers::auth::login)
+    .service(handlers::auth::refresh)
+    .service(handlers::auth::logout)
+    .service(handlers::auth::me)
+    .service(handlers::auth::select_profile)
+    .service(handlers::catalog::get_titles)
+    .service(handlers::catalog::get_title)
+    .service(handlers::catalog::get_season)
+    .service(handlers::catalog::get_episode)
+    .service(handlers::catalog::search)
+    .service(handlers::device::get_user_devices)
+    .service(handlers::device::add_device)
+    .service(handlers::device::delete_device)
+    .service(handlers::device::device_code)
+    .service(handlers::device::verify_device)
+    .service(handlers::device::device_token)
+    .service(handlers::epg::get_epg)
+    .service(handlers::openapi::get_openapi)
+    .service(handlers::openapi::get_docs)
+    .service(handlers::openapi::swagger_ui())
+    .service(handlers::parental::set_pin)
+    .service(handlers::parental::verify_pin)
+    .service(handlers::profiles::get_user_profiles)
+    .service(handlers::profiles::add_profile)
+    .service(handlers::profiles::get_profile)
+    .service(handlers::profiles::update_profile)
+    .service(handlers::profiles::delete_profile)
+    .service(handlers::progress::save_progress)
+    .service(handlers::progress::get_continue_watching)
+    .service(handlers::roles::get_roles)
+    .service(handlers::roles::get_user_roles)
+    .service(handlers::roles::add_user_role)
+    .service(handlers::roles::delete_user_role)
+    .service(handlers::watchlist::get_watchlist)
+    .service(handlers::watchlist::add_watchlist_entry)
+    .service(handlers::watchlist::move_watchlist_entry)
+    .service(handlers::watchlist::delete_watchlist_entry)
+    .service(web::resource("/").route(web::get().to(handle_echo)));
 }
 
 #[actix_web::main]
@@ -181,23 +213,62 @@ async fn main() -> std::io::Result<()> {
         .unwrap();
 
     let config: ExampleConfig = config_.try_deserialize().unwrap();
+    logging::init(&config.log).map_err(IoError::other)?;
+
+    let pg_config = config.pg_config().map_err(IoError::other)?;
+    let connector = tls::make_connector(&config.pg_tls).map_err(IoError::other)?;
+    let pool = pg_config.create_pool(None, connector).unwrap();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.iter().map(String::as_str).collect::<Vec<&str>>()[..] {
+        [] => {}
+        ["migrate", command] => return migrate(&pool, &config.pg_schema, command).await,
+        ["import-xmltv", path] => return import_xmltv(&pool, path).await,
+        _ => {
+            return Err(IoError::new(
+                ErrorKind::InvalidInput,
+                "usage: vidaa-server [migrate <list|apply|verify> | import-xmltv <file>]",
+            ))
+        }
+    }
+
+    let mut client = pool.get().await.map_err(|err| io_error(err.into()))?;
+    if config.run_migrations {
+        let applied = migrations::apply(&mut client, &config.pg_schema)
+            .await
+            .map_err(io_error)?;
+        tracing::info!(count = applied.len(), "applied migrations");
+    }
+    db::prepare_statements(&client).await.map_err(io_error)?;
+    drop(client);
 
-    let pool = config.pg.create_pool(None, NoTls).unwrap();
+    let jwt_keys = web::Data::new(JwtKeys::from_config(&config.jwt).map_err(IoError::other)?);
+    config.device.validate().map_err(IoError::other)?;
+    let device_config = web::Data::new(config.device.clone());
 
     let server = HttpServer::new(move || {
         App::new()
             .app_data(web::Data::new(pool.clone()))
-            .service(
-                web::resource("/users")
-                    .route(web::post().to(add_user))
-                    .route(web::get().to(get_users)),
+            .app_data(jwt_keys.clone())
+            .app_data(device_config.clone())
+            .app_data(
+                web::JsonConfig::default()
+                    .error_handler(|err, _| MyError::BadRequest(err.to_string()).into()),
+            )
+            .app_data(
+                web::QueryConfig::default()
+                    .error_handler(|err, _| MyError::BadRequest(err.to_string()).into()),
+            )
+            .app_data(
+                web::PathConfig::default()
+                    .error_handler(|err, _| MyError::BadRequest(err.to_string()).into()),
             )
-            .service(get_user_by_id)
-            .service(web::resource("/").route(web::get().to(handle_echo)))
+            .configure(routes)
+            .wrap(logging::RequestLogger)
     })
     .bind(config.server_addr.clone())?
     .run();
-    println!("Server running at http://{}/", config.server_addr);
+    tracing::info!(addr = %config.server_addr, "server running");
 
     server.await
 }
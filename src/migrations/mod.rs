@@ -0,0 +1,225 @@
+//! Versioned, forward-only schema migrations embedded in the binary.
+//!
+//! Applied versions are recorded in `schema_migrations` inside the configured
+//! schema together with a checksum of the script, so an edited migration is
+//! caught by `verify` instead of silently diverging from what is deployed.
+//!
+//! Scripts use unqualified table names, connections resolve them through the
+//! `search_path` set up in `ExampleConfig::pg_config`. Extensions are shared by
+//! every schema, they are installed into and qualified with `public`.
+
+use deadpool_postgres::Client;
+use sha2::{Digest, Sha256};
+
+use crate::errors::MyError;
+
+pub struct Migration {
+    pub version: i64,
+    pub name: &'static str,
+    pub sql: &'static str,
+}
+
+impl Migration {
+    pub fn checksum(&self) -> String {
+        format!("{:x}", Sha256::digest(self.sql.as_bytes()))
+    }
+}
+
+/// Every migration known to this build, in the order they are applied.
+pub const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        name: "create_users",
+        sql: include_str!("../../migrations/0001_create_users.sql"),
+    },
+    Migration {
+        version: 2,
+        name: "add_user_password",
+        sql: include_str!("../../migrations/0002_add_user_password.sql"),
+    },
+    Migration {
+        version: 3,
+        name: "create_refresh_tokens",
+        sql: include_str!("../../migrations/0003_create_refresh_tokens.sql"),
+    },
+    Migration {
+        version: 4,
+        name: "create_roles",
+        sql: include_str!("../../migrations/0004_create_roles.sql"),
+    },
+    Migration {
+        version: 5,
+        name: "create_device_codes",
+        sql: include_str!("../../migrations/0005_create_device_codes.sql"),
+    },
+    Migration {
+        version: 6,
+        name: "create_devices",
+        sql: include_str!("../../migrations/0006_create_devices.sql"),
+    },
+    Migration {
+        version: 7,
+        name: "create_profiles",
+        sql: include_str!("../../migrations/0007_create_profiles.sql"),
+    },
+    Migration {
+        version: 8,
+        name: "create_catalog",
+        sql: include_str!("../../migrations/0008_create_catalog.sql"),
+    },
+    Migration {
+        version: 9,
+        name: "create_watch_progress",
+        sql: include_str!("../../migrations/0009_create_watch_progress.sql"),
+    },
+    Migration {
+        version: 10,
+        name: "create_watchlist",
+        sql: include_str!("../../migrations/0010_create_watchlist.sql"),
+    },
+    Migration {
+        version: 11,
+        name: "add_parental_controls",
+        sql: include_str!("../../migrations/0011_add_parental_controls.sql"),
+    },
+    Migration {
+        version: 12,
+        name: "create_epg",
+        sql: include_str!("../../migrations/0012_create_epg.sql"),
+    },
+    Migration {
+        version: 13,
+        name: "add_search",
+        sql: include_str!("../../migrations/0013_add_search.sql"),
+    },
+];
+
+/// Arbitrary key for `pg_advisory_lock` so concurrent instances don't race each other.
+const LOCK_KEY: i64 = 0x7669_6461_6161;
+
+pub struct MigrationStatus {
+    pub version: i64,
+    pub name: &'static str,
+    pub applied_at: Option<String>,
+}
+
+struct AppliedMigration {
+    version: i64,
+    checksum: String,
+    applied_at: String,
+}
+
+async fn ensure_table(client: &Client, schema: &str) -> Result<(), MyError> {
+    // the schema name is validated when the config is loaded
+    client
+        .batch_execute(&format!("CREATE SCHEMA IF NOT EXISTS \"{}\";", schema))
+        .await?;
+    client
+        .batch_execute(include_str!("../../sql/create_schema_migrations.sql"))
+        .await?;
+    Ok(())
+}
+
+async fn applied(client: &Client, schema: &str) -> Result<Vec<AppliedMigration>, MyError> {
+    ensure_table(client, schema).await?;
+
+    let stmt = client
+        .prepare(include_str!("../../sql/get_schema_migrations.sql"))
+        .await?;
+
+    Ok(client
+        .query(&stmt, &[])
+        .await?
+        .iter()
+        .map(|row| AppliedMigration {
+            version: row.get("version"),
+            checksum: row.get("checksum"),
+            applied_at: row.get("applied_at"),
+        })
+        .collect())
+}
+
+pub async fn status(client: &Client, schema: &str) -> Result<Vec<MigrationStatus>, MyError> {
+    let applied = applied(client, schema).await?;
+
+    Ok(MIGRATIONS
+        .iter()
+        .map(|migration| MigrationStatus {
+            version: migration.version,
+            name: migration.name,
+            applied_at: applied
+                .iter()
+                .find(|applied| applied.version == migration.version)
+                .map(|applied| applied.applied_at.clone()),
+        })
+        .collect())
+}
+
+/// Returns a description of every applied migration that differs from this
+/// build, an empty list means the schema is in a known state. Pending
+/// migrations are not problems here, `status` lists them.
+pub async fn verify(client: &Client, schema: &str) -> Result<Vec<String>, MyError> {
+    let applied = applied(client, schema).await?;
+
+    Ok(applied
+        .iter()
+        .filter_map(
+            |applied| match MIGRATIONS.iter().find(|m| m.version == applied.version) {
+                None => Some(format!(
+                    "migration {} is applied but unknown to this build",
+                    applied.version
+                )),
+                Some(migration) if migration.checksum() != applied.checksum => Some(format!(
+                    "migration {} ({}) was modified after being applied",
+                    migration.version, migration.name
+                )),
+                Some(_) => None,
+            },
+        )
+        .collect())
+}
+
+/// Applies all pending migrations, each in its own transaction, and returns the
+/// versions that were applied. Refuses to run when `verify` reports problems.
+pub async fn apply(client: &mut Client, schema: &str) -> Result<Vec<i64>, MyError> {
+    client
+        .execute("SELECT pg_advisory_lock($1)", &[&LOCK_KEY])
+        .await?;
+
+    let result = apply_pending(client, schema).await;
+
+    client
+        .execute("SELECT pg_advisory_unlock($1)", &[&LOCK_KEY])
+        .await?;
+
+    result
+}
+
+async fn apply_pending(client: &mut Client, schema: &str) -> Result<Vec<i64>, MyError> {
+    let problems = verify(client, schema).await?;
+    if !problems.is_empty() {
+        return Err(MyError::MigrationError(problems.join("; ")));
+    }
+
+    let applied = applied(client, schema).await?;
+    let mut versions = Vec::new();
+
+    for migration in MIGRATIONS
+        .iter()
+        .filter(|m| !applied.iter().any(|a| a.version == m.version))
+    {
+        let transaction = client.transaction().await?;
+        transaction.batch_execute(migration.sql).await?;
+        transaction
+            .execute(
+                include_str!("../../sql/add_schema_migration.sql"),
+                &[&migration.version, &migration.name, &migration.checksum()],
+            )
+            .await?;
+        transaction.commit().await?;
+
+        versions.push(migration.version);
+    }
+
+    Ok(versions)
+}
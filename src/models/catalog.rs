@@ -0,0 +1,123 @@
+use serde::{Deserialize, Serialize};
+use tokio_pg_mapper_derive::PostgresMapper;
+
+use super::UserHit;
+
+#[derive(PostgresMapper, Serialize)]
+#[pg_mapper(table = "titles")]
+pub struct Title {
+    pub id: i64,
+    /// `movie` or `series`.
+    pub kind: String,
+    pub name: String,
+    pub synopsis: String,
+    pub genres: Vec<String>,
+    pub release_year: i32,
+    /// Minimum viewer age, e.g. 0, 6, 12, 16 or 18.
+    pub age_rating: i16,
+}
+
+#[derive(PostgresMapper, Serialize)]
+#[pg_mapper(table = "seasons")]
+pub struct Season {
+    pub id: i64,
+    pub title_id: i64,
+    pub number: i32,
+    pub name: String,
+    pub release_year: Option<i32>,
+}
+
+#[derive(PostgresMapper, Serialize)]
+#[pg_mapper(table = "episodes")]
+pub struct Episode {
+    pub id: i64,
+    pub season_id: i64,
+    pub number: i32,
+    pub name: String,
+    pub synopsis: String,
+    pub duration_secs: i32,
+}
+
+/// Stream or artwork of a title or of a single episode.
+#[derive(PostgresMapper, Serialize)]
+#[pg_mapper(table = "assets")]
+pub struct Asset {
+    pub id: i64,
+    pub title_id: Option<i64>,
+    pub episode_id: Option<i64>,
+    /// `video`, `trailer`, `poster` or `backdrop`.
+    pub kind: String,
+    pub url: String,
+    pub mime_type: String,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub duration_secs: Option<i32>,
+}
+
+/// Query string accepted by `GET /titles`.
+#[derive(Deserialize)]
+pub struct TitleQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    pub genre: Option<String>,
+    pub year: Option<i32>,
+    /// Only titles suitable for this age, e.g. `12` also returns titles rated 0 and 6.
+    pub max_age_rating: Option<i16>,
+    pub kind: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct TitlePage {
+    pub items: Vec<Title>,
+    pub total: i64,
+}
+
+#[derive(Serialize)]
+pub struct TitleDetail {
+    #[serde(flatten)]
+    pub title: Title,
+    pub seasons: Vec<Season>,
+    pub assets: Vec<Asset>,
+}
+
+#[derive(Serialize)]
+pub struct SeasonDetail {
+    #[serde(flatten)]
+    pub season: Season,
+    pub episodes: Vec<Episode>,
+}
+
+#[derive(Serialize)]
+pub struct EpisodeDetail {
+    #[serde(flatten)]
+    pub episode: Episode,
+    pub assets: Vec<Asset>,
+}
+
+/// Query string accepted by `GET /search`.
+#[derive(Deserialize)]
+pub struct SearchQuery {
+    pub q: String,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct SearchHit {
+    #[serde(flatten)]
+    pub title: Title,
+    /// The name with matched words wrapped in `<b>` tags, `None` for fuzzy matches.
+    pub highlight: Option<String>,
+    /// Excerpt of the synopsis around the matched words.
+    pub snippet: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct SearchResults {
+    pub items: Vec<SearchHit>,
+    /// Whether nothing matched the words as typed and the items are similar names instead.
+    pub fuzzy: bool,
+    /// Matching users, only included for callers with the `users:read` permission.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub users: Option<Vec<UserHit>>,
+}
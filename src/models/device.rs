@@ -0,0 +1,115 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio_pg_mapper_derive::PostgresMapper;
+use validator::Validate;
+
+use crate::errors::MyError;
+
+pub const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+#[derive(PostgresMapper, Serialize)]
+#[pg_mapper(table = "devices")]
+pub struct Device {
+    pub id: i64,
+    pub user_id: i64,
+    pub model: String,
+    pub firmware_version: String,
+    pub os_version: String,
+    pub app_version: String,
+    pub last_seen_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Details reported by a TV, `os_version` is the VIDAA OS version.
+#[derive(Deserialize, Validate)]
+pub struct NewDevice {
+    #[validate(length(min = 1, max = 100, message = "must be between 1 and 100 characters"))]
+    pub model: String,
+    #[validate(length(min = 1, max = 50, message = "must be between 1 and 50 characters"))]
+    pub firmware_version: String,
+    #[validate(length(min = 1, max = 50, message = "must be between 1 and 50 characters"))]
+    pub os_version: String,
+    #[validate(length(min = 1, max = 50, message = "must be between 1 and 50 characters"))]
+    pub app_version: String,
+}
+
+/// Form encoded device authorization request. Clients are not registered, so
+/// `client_id` and `scope` are accepted but not read.
+#[derive(Deserialize)]
+pub struct DeviceCodeRequest {
+    pub model: Option<String>,
+    pub firmware_version: Option<String>,
+    pub os_version: Option<String>,
+    pub app_version: Option<String>,
+}
+
+impl DeviceCodeRequest {
+    /// The TV's details, which are sent all together or not at all.
+    pub fn device(self) -> Result<Option<NewDevice>, MyError> {
+        match (
+            self.model,
+            self.firmware_version,
+            self.os_version,
+            self.app_version,
+        ) {
+            (Some(model), Some(firmware_version), Some(os_version), Some(app_version)) => {
+                let device = NewDevice {
+                    model,
+                    firmware_version,
+                    os_version,
+                    app_version,
+                };
+                device.validate()?;
+                Ok(Some(device))
+            }
+            (None, None, None, None) => Ok(None),
+            _ => Err(MyError::BadRequest(
+                "model, firmware_version, os_version and app_version must be sent together".into(),
+            )),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct DeviceCodeResponse {
+    pub device_code: String,
+    pub user_code: String,
+    pub verification_uri: String,
+    pub verification_uri_complete: String,
+    pub expires_in: u64,
+    pub interval: u64,
+}
+
+#[derive(Deserialize)]
+pub struct DeviceVerifyRequest {
+    pub user_code: String,
+    /// `false` denies the request, the TV then gets `access_denied`.
+    #[serde(default = "default_approve")]
+    pub approve: bool,
+    /// Parental PIN, required to approve from a token scoped to a profile.
+    pub pin: Option<String>,
+}
+
+fn default_approve() -> bool {
+    true
+}
+
+/// Form encoded access token request of the device flow, `client_id` is not read.
+#[derive(Deserialize)]
+pub struct DeviceTokenRequest {
+    pub grant_type: String,
+    pub device_code: String,
+}
+
+/// Outcome of a `/device/token` poll.
+pub enum DevicePoll {
+    Pending,
+    SlowDown,
+    Denied,
+    Expired,
+    /// The device is registered to the user when its details were sent with the code.
+    Approved {
+        user_id: i64,
+        device: Option<NewDevice>,
+    },
+}
@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio_pg_mapper_derive::PostgresMapper;
+
+#[derive(PostgresMapper, Serialize)]
+#[pg_mapper(table = "channels")]
+pub struct Channel {
+    pub id: i64,
+    pub xmltv_id: String,
+    pub name: String,
+    pub icon: Option<String>,
+}
+
+#[derive(PostgresMapper, Serialize)]
+#[pg_mapper(table = "programs")]
+pub struct Program {
+    pub id: i64,
+    pub channel_id: i64,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub description: Option<String>,
+    pub category: Option<String>,
+}
+
+/// A channel as read from an XMLTV file.
+pub struct NewChannel {
+    pub xmltv_id: String,
+    pub name: String,
+    pub icon: Option<String>,
+}
+
+/// A programme as read from an XMLTV file, `channel` is the XMLTV channel id.
+pub struct NewProgram {
+    pub channel: String,
+    pub starts_at: DateTime<Utc>,
+    pub ends_at: DateTime<Utc>,
+    pub title: String,
+    pub subtitle: Option<String>,
+    pub description: Option<String>,
+    pub category: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct EpgQuery {
+    /// Every channel when left out.
+    pub channel: Option<i64>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Programs of a channel overlapping the requested window.
+#[derive(Serialize)]
+pub struct ChannelSchedule {
+    #[serde(flatten)]
+    pub channel: Channel,
+    pub programs: Vec<Program>,
+}
+
+pub struct ImportSummary {
+    pub channels: usize,
+    pub programs: usize,
+    /// Programmes of channels missing from the file.
+    pub skipped: usize,
+}
@@ -0,0 +1,162 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tokio_pg_mapper_derive::PostgresMapper;
+use utoipa::{IntoParams, ToSchema};
+use validator::Validate;
+
+pub mod auth;
+pub mod catalog;
+pub mod device;
+pub mod epg;
+pub mod parental;
+pub mod profiles;
+pub mod progress;
+pub mod roles;
+pub mod watchlist;
+
+lazy_static! {
+    static ref USERNAME: Regex = Regex::new(r"^[A-Za-z0-9_.-]+$").unwrap();
+}
+
+#[derive(Deserialize, PostgresMapper, Serialize, ToSchema)]
+#[pg_mapper(table = "users")] // singular 'user' is a keyword..
+pub struct User {
+    pub id: i64,
+    pub email: String,
+    pub first_name: String,
+    pub last_name: String,
+    pub username: String,
+}
+
+/// A user found by `GET /search`.
+#[derive(Serialize)]
+pub struct UserHit {
+    #[serde(flatten)]
+    pub user: User,
+    /// `First Last (username)` with matched words wrapped in `<b>` tags, `None`
+    /// for fuzzy matches.
+    pub highlight: Option<String>,
+}
+
+/// Payload for creating a user, the id is assigned by the database.
+/// Length limits mirror the columns in `migrations/0001_create_users.sql`.
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct NewUser {
+    #[validate(
+        email(message = "must be a valid email address"),
+        length(max = 200, message = "must be at most 200 characters")
+    )]
+    pub email: String,
+    #[validate(length(min = 1, max = 200, message = "must be between 1 and 200 characters"))]
+    pub first_name: String,
+    #[validate(length(min = 1, max = 200, message = "must be between 1 and 200 characters"))]
+    pub last_name: String,
+    #[validate(
+        length(min = 3, max = 50, message = "must be between 3 and 50 characters"),
+        regex(
+            path = "USERNAME",
+            message = "may only contain letters, digits, '_', '.' and '-'"
+        )
+    )]
+    pub username: String,
+    #[validate(length(min = 8, max = 128, message = "must be between 8 and 128 characters"))]
+    pub password: String,
+}
+
+impl NewUser {
+    /// Trims surrounding whitespace and lowercases the email before validation.
+    pub fn normalize(self) -> Self {
+        NewUser {
+            email: self.email.trim().to_lowercase(),
+            first_name: self.first_name.trim().to_string(),
+            last_name: self.last_name.trim().to_string(),
+            username: self.username.trim().to_string(),
+            password: self.password,
+        }
+    }
+}
+
+/// Changes to apply to an existing user, fields left as `None` are kept as they are.
+#[derive(Deserialize, ToSchema, Validate)]
+pub struct UpdateUser {
+    #[validate(
+        email(message = "must be a valid email address"),
+        length(max = 200, message = "must be at most 200 characters")
+    )]
+    pub email: Option<String>,
+    #[validate(length(min = 1, max = 200, message = "must be between 1 and 200 characters"))]
+    pub first_name: Option<String>,
+    #[validate(length(min = 1, max = 200, message = "must be between 1 and 200 characters"))]
+    pub last_name: Option<String>,
+    #[validate(
+        length(min = 3, max = 50, message = "must be between 3 and 50 characters"),
+        regex(
+            path = "USERNAME",
+            message = "may only contain letters, digits, '_', '.' and '-'"
+        )
+    )]
+    pub username: Option<String>,
+    #[validate(length(min = 8, max = 128, message = "must be between 8 and 128 characters"))]
+    pub password: Option<String>,
+    /// Required with `password` when users change their own password.
+    pub current_password: Option<String>,
+}
+
+impl UpdateUser {
+    pub fn normalize(self) -> Self {
+        UpdateUser {
+            email: self.email.map(|email| email.trim().to_lowercase()),
+            first_name: self.first_name.map(|name| name.trim().to_string()),
+            last_name: self.last_name.map(|name| name.trim().to_string()),
+            username: self.username.map(|name| name.trim().to_string()),
+            password: self.password,
+            current_password: self.current_password,
+        }
+    }
+}
+
+impl From<NewUser> for UpdateUser {
+    fn from(user: NewUser) -> Self {
+        UpdateUser {
+            email: Some(user.email),
+            first_name: Some(user.first_name),
+            last_name: Some(user.last_name),
+            username: Some(user.username),
+            password: Some(user.password),
+            current_password: None,
+        }
+    }
+}
+
+/// Payload for replacing a user, `current_password` is required when users
+/// replace their own account since that sets a new password.
+#[derive(Deserialize, ToSchema)]
+pub struct ReplaceUser {
+    #[serde(flatten)]
+    pub user: NewUser,
+    pub current_password: Option<String>,
+}
+
+/// Query string accepted by `GET /users`.
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct UserQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    /// Id of the last user of the previous page, only valid when sorting by id.
+    pub cursor: Option<i64>,
+    /// Comma separated fields, prefixed with `-` for descending order, e.g. `username,-email`.
+    pub sort: Option<String>,
+    pub username: Option<String>,
+    pub email_domain: Option<String>,
+    /// Matches part of the first or last name.
+    pub q: Option<String>,
+}
+
+#[derive(Serialize, ToSchema)]
+pub struct UserPage {
+    pub items: Vec<User>,
+    pub total: i64,
+    pub next_cursor: Option<i64>,
+}
@@ -0,0 +1,21 @@
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::Deserialize;
+use validator::Validate;
+
+lazy_static! {
+    static ref PIN: Regex = Regex::new(r"^[0-9]{4,6}$").unwrap();
+}
+
+#[derive(Deserialize, Validate)]
+pub struct SetPinRequest {
+    #[validate(regex(path = "PIN", message = "must be 4 to 6 digits"))]
+    pub pin: String,
+    /// Required when the account already has a PIN.
+    pub current_pin: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct VerifyPinRequest {
+    pub pin: String,
+}
@@ -0,0 +1,111 @@
+use chrono::{DateTime, Utc};
+use lazy_static::lazy_static;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use tokio_pg_mapper_derive::PostgresMapper;
+use validator::Validate;
+
+lazy_static! {
+    /// BCP 47 language with optional region, e.g. `en` or `de-DE`.
+    static ref LANGUAGE: Regex = Regex::new(r"^[a-z]{2,3}(-[A-Z]{2})?$").unwrap();
+}
+
+/// Age rating limit of profiles that see the whole catalog.
+pub const MAX_AGE_RATING: i16 = 18;
+/// Default limit of kids profiles.
+pub const KIDS_MAX_AGE_RATING: i16 = 6;
+
+#[derive(PostgresMapper, Serialize)]
+#[pg_mapper(table = "profiles")]
+pub struct Profile {
+    pub id: i64,
+    pub user_id: i64,
+    pub name: String,
+    pub avatar: Option<String>,
+    pub is_kids: bool,
+    pub language: String,
+    pub max_age_rating: i16,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct NewProfile {
+    #[validate(length(min = 1, max = 50, message = "must be between 1 and 50 characters"))]
+    pub name: String,
+    #[validate(length(max = 200, message = "must be at most 200 characters"))]
+    pub avatar: Option<String>,
+    #[serde(default)]
+    pub is_kids: bool,
+    #[validate(regex(
+        path = "LANGUAGE",
+        message = "must be a language tag like 'en' or 'de-DE'"
+    ))]
+    #[serde(default = "default_language")]
+    pub language: String,
+    #[validate(range(min = 0, max = 18, message = "must be between 0 and 18"))]
+    pub max_age_rating: Option<i16>,
+    /// Parental PIN, required from a restricted profile to create a less restricted one.
+    pub pin: Option<String>,
+}
+
+impl NewProfile {
+    pub fn normalize(self) -> Self {
+        NewProfile {
+            name: self.name.trim().to_string(),
+            avatar: self.avatar.map(|avatar| avatar.trim().to_string()),
+            max_age_rating: self.max_age_rating.or(Some(match self.is_kids {
+                true => KIDS_MAX_AGE_RATING,
+                false => MAX_AGE_RATING,
+            })),
+            ..self
+        }
+    }
+}
+
+fn default_language() -> String {
+    "en".into()
+}
+
+/// Changes to apply to an existing profile, fields left as `None` are kept as they are.
+#[derive(Deserialize, Validate)]
+pub struct UpdateProfile {
+    #[validate(length(min = 1, max = 50, message = "must be between 1 and 50 characters"))]
+    pub name: Option<String>,
+    #[validate(length(max = 200, message = "must be at most 200 characters"))]
+    pub avatar: Option<String>,
+    pub is_kids: Option<bool>,
+    #[validate(regex(
+        path = "LANGUAGE",
+        message = "must be a language tag like 'en' or 'de-DE'"
+    ))]
+    pub language: Option<String>,
+    #[validate(range(min = 0, max = 18, message = "must be between 0 and 18"))]
+    pub max_age_rating: Option<i16>,
+    /// Parental PIN, required to change `is_kids` or `max_age_rating` once the account has one.
+    pub pin: Option<String>,
+}
+
+impl UpdateProfile {
+    pub fn normalize(self) -> Self {
+        UpdateProfile {
+            name: self.name.map(|name| name.trim().to_string()),
+            avatar: self.avatar.map(|avatar| avatar.trim().to_string()),
+            max_age_rating: match self.is_kids {
+                Some(true) => self.max_age_rating.or(Some(KIDS_MAX_AGE_RATING)),
+                _ => self.max_age_rating,
+            },
+            ..self
+        }
+    }
+
+    pub fn changes_restrictions(&self) -> bool {
+        self.is_kids.is_some() || self.max_age_rating.is_some()
+    }
+}
+
+#[derive(Deserialize)]
+pub struct SelectProfileRequest {
+    pub profile_id: i64,
+    /// Parental PIN, required to leave a profile for one with a higher age rating limit.
+    pub pin: Option<String>,
+}
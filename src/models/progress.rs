@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio_pg_mapper_derive::PostgresMapper;
+use validator::Validate;
+
+use super::catalog::Title;
+
+#[derive(PostgresMapper, Serialize)]
+#[pg_mapper(table = "watch_progress")]
+pub struct WatchProgress {
+    pub profile_id: i64,
+    pub title_id: i64,
+    pub episode_id: Option<i64>,
+    pub position_secs: i32,
+    pub duration_secs: i32,
+    pub completed: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Playback position reported by a TV, for a movie or for an episode.
+#[derive(Deserialize, Validate)]
+pub struct NewProgress {
+    pub title_id: Option<i64>,
+    pub episode_id: Option<i64>,
+    #[validate(range(min = 0, message = "must not be negative"))]
+    pub position_secs: i32,
+    #[validate(range(min = 1, message = "must be positive"))]
+    pub duration_secs: i32,
+}
+
+/// Query string accepted by `GET /profiles/{profile_id}/continue-watching`.
+#[derive(Deserialize)]
+pub struct ContinueWatchingQuery {
+    pub limit: Option<i64>,
+}
+
+#[derive(Serialize)]
+pub struct ContinueWatchingItem {
+    #[serde(flatten)]
+    pub progress: WatchProgress,
+    pub title: Title,
+}
@@ -0,0 +1,10 @@
+use serde::Serialize;
+use tokio_pg_mapper_derive::PostgresMapper;
+
+#[derive(PostgresMapper, Serialize)]
+#[pg_mapper(table = "roles")]
+pub struct Role {
+    pub name: String,
+    pub description: String,
+    pub permissions: Vec<String>,
+}
@@ -0,0 +1,49 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio_pg_mapper_derive::PostgresMapper;
+use validator::Validate;
+
+use super::catalog::Title;
+
+#[derive(PostgresMapper, Serialize)]
+#[pg_mapper(table = "watchlist")]
+pub struct WatchlistEntry {
+    pub profile_id: i64,
+    pub title_id: i64,
+    pub position: i32,
+    pub added_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct NewWatchlistEntry {
+    pub title_id: i64,
+}
+
+#[derive(Deserialize, Validate)]
+pub struct MoveWatchlistEntry {
+    /// New 1-based position, positions past the end move the entry last.
+    #[validate(range(min = 1, message = "must be at least 1"))]
+    pub position: i32,
+}
+
+/// Query string accepted by `GET /profiles/{profile_id}/watchlist`.
+#[derive(Deserialize)]
+pub struct WatchlistQuery {
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+    /// `position` (default), `added_at` or `-added_at` for the latest first.
+    pub sort: Option<String>,
+}
+
+#[derive(Serialize)]
+pub struct WatchlistItem {
+    #[serde(flatten)]
+    pub entry: WatchlistEntry,
+    pub title: Title,
+}
+
+#[derive(Serialize)]
+pub struct WatchlistPage {
+    pub items: Vec<WatchlistItem>,
+    pub total: i64,
+}
@@ -0,0 +1,220 @@
+//! OpenAPI 3 document of the user endpoints and the login, paths and methods are read from
+//! the actix route macros so they cannot disagree with the handlers.
+
+use utoipa::{
+    openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme},
+    Modify, OpenApi,
+};
+
+use crate::{
+    errors::ErrorBody,
+    handlers,
+    models::{
+        auth::{LoginRequest, TokenResponse},
+        NewUser, ReplaceUser, UpdateUser, User, UserPage,
+    },
+};
+
+#[derive(OpenApi)]
+#[openapi(
+    paths(
+        handlers::add_user,
+        handlers::get_users,
+        handlers::get_user_by_id,
+        handlers::replace_user,
+        handlers::update_user,
+        handlers::delete_user,
+        handlers::auth::login,
+    ),
+    components(schemas(
+        User,
+        NewUser,
+        ReplaceUser,
+        UpdateUser,
+        UserPage,
+        LoginRequest,
+        TokenResponse,
+        ErrorBody
+    )),
+    modifiers(&BearerAuth),
+    tags(
+        (name = "users", description = "User accounts"),
+        (name = "auth", description = "Sign in"),
+    )
+)]
+pub struct ApiDoc;
+
+/// Access tokens from `POST /auth/login`, sent as `Authorization: Bearer <token>`.
+struct BearerAuth;
+
+impl Modify for BearerAuth {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use actix_web::{
+        dev::{Service, ServiceResponse},
+        http::{Method, StatusCode},
+        test, web, App, Error, HttpResponse,
+    };
+    use regex::Regex;
+    use utoipa::{openapi::PathItemType, OpenApi};
+
+    use super::ApiDoc;
+    use crate::ROUTES;
+
+    /// Answered by the app for requests no route matches.
+    const UNROUTED: StatusCode = StatusCode::IM_A_TEAPOT;
+
+    const METHODS: [(PathItemType, Method); 5] = [
+        (PathItemType::Get, Method::GET),
+        (PathItemType::Post, Method::POST),
+        (PathItemType::Put, Method::PUT),
+        (PathItemType::Patch, Method::PATCH),
+        (PathItemType::Delete, Method::DELETE),
+    ];
+
+    /// Routes left out of the spec on purpose, it covers the user accounts and
+    /// the login the bearer tokens come from.
+    const UNDOCUMENTED: &[(&str, &str)] = &[
+        ("POST", "/auth/refresh"),
+        ("POST", "/auth/logout"),
+        ("GET", "/auth/me"),
+        ("POST", "/auth/profile"),
+        ("GET", "/titles"),
+        ("GET", "/titles/{title_id}"),
+        ("GET", "/seasons/{season_id}"),
+        ("GET", "/episodes/{episode_id}"),
+        ("GET", "/search"),
+        ("GET", "/users/{user_id}/devices"),
+        ("POST", "/users/{user_id}/devices"),
+        ("DELETE", "/users/{user_id}/devices/{device_id}"),
+        ("POST", "/device/code"),
+        ("POST", "/device/verify"),
+        ("POST", "/device/token"),
+        ("GET", "/epg"),
+        ("GET", "/openapi.json"),
+        ("GET", "/docs"),
+        ("GET", "/docs/{_:.*}"),
+        ("PUT", "/users/{user_id}/parental-pin"),
+        ("POST", "/users/{user_id}/parental-pin/verify"),
+        ("GET", "/users/{user_id}/profiles"),
+        ("POST", "/users/{user_id}/profiles"),
+        ("GET", "/users/{user_id}/profiles/{profile_id}"),
+        ("PATCH", "/users/{user_id}/profiles/{profile_id}"),
+        ("DELETE", "/users/{user_id}/profiles/{profile_id}"),
+        ("POST", "/profiles/{profile_id}/progress"),
+        ("GET", "/profiles/{profile_id}/continue-watching"),
+        ("GET", "/roles"),
+        ("GET", "/users/{user_id}/roles"),
+        ("PUT", "/users/{user_id}/roles/{role}"),
+        ("DELETE", "/users/{user_id}/roles/{role}"),
+        ("GET", "/profiles/{profile_id}/watchlist"),
+        ("POST", "/profiles/{profile_id}/watchlist"),
+        ("PATCH", "/profiles/{profile_id}/watchlist/{title_id}"),
+        ("DELETE", "/profiles/{profile_id}/watchlist/{title_id}"),
+        ("GET", "/"),
+    ];
+
+    /// `ROUTES` lists exactly the methods the app answers on each of its paths.
+    #[actix_web::test]
+    async fn routes_match_registered_routes() {
+        let app = test::init_service(
+            App::new()
+                .configure(crate::routes)
+                .default_service(web::to(|| async { HttpResponse::new(UNROUTED) })),
+        )
+        .await;
+        let path_param = Regex::new(r"\{[^}]+\}").unwrap();
+
+        let paths: BTreeSet<&str> = ROUTES.iter().map(|(_, path)| *path).collect();
+        for path in paths {
+            let uri = path_param.replace_all(path, "1");
+            for (_, method) in METHODS {
+                let request = test::TestRequest::default()
+                    .method(method.clone())
+                    .uri(&uri)
+                    .to_request();
+                let routed = is_routed(app.call(request).await);
+                let listed = ROUTES.contains(&(method.as_str(), path));
+
+                assert_eq!(
+                    routed, listed,
+                    "{} {} is routed: {}, listed in ROUTES: {}",
+                    method, path, routed, listed
+                );
+            }
+        }
+    }
+
+    /// Every route is either documented or in `UNDOCUMENTED`, and the spec
+    /// only documents routes.
+    #[actix_web::test]
+    async fn spec_matches_routes() {
+        let spec = ApiDoc::openapi();
+        let documented: BTreeSet<(&str, &str)> = spec
+            .paths
+            .paths
+            .iter()
+            .flat_map(|(path, item)| {
+                METHODS
+                    .iter()
+                    .filter(|(item_type, _)| item.operations.contains_key(item_type))
+                    .map(move |(_, method)| (method.as_str(), path.as_str()))
+            })
+            .collect();
+        let routes: BTreeSet<(&str, &str)> = ROUTES.iter().copied().collect();
+        let undocumented: BTreeSet<(&str, &str)> = UNDOCUMENTED.iter().copied().collect();
+
+        for route in &documented {
+            assert!(
+                routes.contains(route),
+                "{:?} is documented but not routed",
+                route
+            );
+            assert!(
+                !undocumented.contains(route),
+                "{:?} is documented but listed in UNDOCUMENTED",
+                route
+            );
+        }
+        for route in &routes {
+            assert!(
+                documented.contains(route) || undocumented.contains(route),
+                "{:?} is routed but neither documented nor in UNDOCUMENTED",
+                route
+            );
+        }
+        for route in &undocumented {
+            assert!(
+                routes.contains(route),
+                "{:?} in UNDOCUMENTED is not routed",
+                route
+            );
+        }
+    }
+
+    /// Resources matching the path but not the method answer 405, middleware
+    /// such as `RequireAuth` rejects with an error rather than a response.
+    fn is_routed(result: Result<ServiceResponse, Error>) -> bool {
+        let status = match result {
+            Ok(response) => response.status(),
+            Err(err) => err.as_response_error().status_code(),
+        };
+        status != UNROUTED && status != StatusCode::METHOD_NOT_ALLOWED
+    }
+}
@@ -0,0 +1,117 @@
+//! rustls connector for the Postgres pool, following libpq's `sslmode` semantics:
+//! `prefer` and `require` encrypt without checking the server certificate,
+//! `verify-full` checks the chain against `ca_file` (or the webpki roots) and the host name.
+
+use std::{fs::File, io::BufReader, sync::Arc};
+
+use rustls::{
+    client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier},
+    crypto::{ring, verify_tls12_signature, verify_tls13_signature, CryptoProvider},
+    pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime},
+    ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+};
+use tokio_postgres_rustls::MakeRustlsConnect;
+
+use crate::config::{TlsConfig, TlsMode};
+
+pub fn make_connector(config: &TlsConfig) -> Result<MakeRustlsConnect, String> {
+    let provider = Arc::new(ring::default_provider());
+    let builder = ClientConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions()
+        .map_err(|err| err.to_string())?;
+
+    let builder = match config.mode {
+        TlsMode::VerifyFull => builder.with_root_certificates(root_store(config)?),
+        TlsMode::Disable | TlsMode::Prefer | TlsMode::Require => builder
+            .dangerous()
+            .with_custom_certificate_verifier(Arc::new(NoVerification(provider))),
+    };
+
+    let client_config = match (&config.cert_file, &config.key_file) {
+        (Some(cert_file), Some(key_file)) => builder
+            .with_client_auth_cert(load_certs(cert_file)?, load_key(key_file)?)
+            .map_err(|err| format!("invalid client certificate: {}", err))?,
+        (None, None) => builder.with_no_client_auth(),
+        _ => return Err("PG_TLS.CERT_FILE and PG_TLS.KEY_FILE must be set together".into()),
+    };
+
+    Ok(MakeRustlsConnect::new(client_config))
+}
+
+fn root_store(config: &TlsConfig) -> Result<RootCertStore, String> {
+    let mut roots = RootCertStore::empty();
+    match config.ca_file {
+        Some(ref ca_file) => {
+            for cert in load_certs(ca_file)? {
+                roots
+                    .add(cert)
+                    .map_err(|err| format!("invalid CA certificate in {}: {}", ca_file, err))?;
+            }
+        }
+        None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+    }
+    Ok(roots)
+}
+
+fn load_certs(path: &str) -> Result<Vec<CertificateDer<'static>>, String> {
+    let file = File::open(path).map_err(|err| format!("cannot open {}: {}", path, err))?;
+    rustls_pemfile::certs(&mut BufReader::new(file))
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|err| format!("cannot read certificates from {}: {}", path, err))
+}
+
+fn load_key(path: &str) -> Result<PrivateKeyDer<'static>, String> {
+    let file = File::open(path).map_err(|err| format!("cannot open {}: {}", path, err))?;
+    rustls_pemfile::private_key(&mut BufReader::new(file))
+        .map_err(|err| format!("cannot read private key from {}: {}", path, err))?
+        .ok_or_else(|| format!("no private key found in {}", path))
+}
+
+/// Accepts any server certificate while still checking handshake signatures.
+#[derive(Debug)]
+struct NoVerification(Arc<CryptoProvider>);
+
+impl ServerCertVerifier for NoVerification {
+    fn verify_server_cert(
+        &self,
+        _end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        Ok(ServerCertVerified::assertion())
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.0.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.0.signature_verification_algorithms.supported_schemes()
+    }
+}
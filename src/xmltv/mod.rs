@@ -0,0 +1,309 @@
+//! Streaming reader for XMLTV guide files, see <https://wiki.xmltv.org/index.php/XMLTVFormat>.
+//! Programmes without a `stop` time end when the next one on the channel starts,
+//! of repeated elements like `title` in several languages the first one is kept.
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+};
+
+use chrono::{DateTime, Utc};
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+
+use crate::models::epg::{NewChannel, NewProgram};
+
+#[derive(Default)]
+pub struct Guide {
+    pub channels: Vec<NewChannel>,
+    pub programs: Vec<NewProgram>,
+    /// Programmes left out for a missing channel, start, stop or title.
+    pub invalid: usize,
+}
+
+#[derive(Default)]
+struct PartialChannel {
+    id: Option<String>,
+    name: Option<String>,
+    icon: Option<String>,
+}
+
+#[derive(Default)]
+struct PartialProgram {
+    channel: Option<String>,
+    starts_at: Option<DateTime<Utc>>,
+    ends_at: Option<DateTime<Utc>>,
+    title: Option<String>,
+    subtitle: Option<String>,
+    description: Option<String>,
+    category: Option<String>,
+}
+
+/// Text elements the reader is currently inside of.
+#[derive(Clone, Copy)]
+enum Field {
+    DisplayName,
+    Title,
+    SubTitle,
+    Desc,
+    Category,
+}
+
+pub fn read_file(path: &str) -> Result<Guide, String> {
+    let file = File::open(path).map_err(|err| format!("cannot open {}: {}", path, err))?;
+    parse(BufReader::new(file)).map_err(|err| format!("cannot read {}: {}", path, err))
+}
+
+fn parse<R: BufRead>(input: R) -> Result<Guide, quick_xml::Error> {
+    let mut reader = Reader::from_reader(input);
+    reader.config_mut().trim_text(true);
+
+    let mut channels = Vec::new();
+    let mut programs = Vec::new();
+    let mut channel: Option<PartialChannel> = None;
+    let mut program: Option<PartialProgram> = None;
+    let mut field: Option<Field> = None;
+    let mut buf = Vec::new();
+
+    loop {
+        buf.clear();
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(element) => match element.name().as_ref() {
+                b"channel" => {
+                    channel = Some(PartialChannel {
+                        id: attribute(&element, b"id")?,
+                        ..Default::default()
+                    })
+                }
+                b"programme" => {
+                    program = Some(PartialProgram {
+                        channel: attribute(&element, b"channel")?,
+                        starts_at: attribute(&element, b"start")?
+                            .as_deref()
+                            .and_then(parse_time),
+                        ends_at: attribute(&element, b"stop")?
+                            .as_deref()
+                            .and_then(parse_time),
+                        ..Default::default()
+                    })
+                }
+                b"icon" => set_icon(&mut channel, &element)?,
+                b"display-name" => field = Some(Field::DisplayName),
+                b"title" => field = Some(Field::Title),
+                b"sub-title" => field = Some(Field::SubTitle),
+                b"desc" => field = Some(Field::Desc),
+                b"category" => field = Some(Field::Category),
+                _ => field = None,
+            },
+            Event::Empty(element) if element.name().as_ref() == b"icon" => {
+                set_icon(&mut channel, &element)?
+            }
+            Event::Text(text) => {
+                if let Some(slot) = text_slot(field, &mut channel, &mut program) {
+                    slot.get_or_insert(text.unescape()?.into_owned());
+                }
+            }
+            Event::CData(data) => {
+                if let Some(slot) = text_slot(field, &mut channel, &mut program) {
+                    slot.get_or_insert(String::from_utf8_lossy(&data).into_owned());
+                }
+            }
+            Event::End(element) => {
+                field = None;
+                match element.name().as_ref() {
+                    b"channel" => channels.extend(channel.take()),
+                    b"programme" => programs.extend(program.take()),
+                    _ => {}
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    let channels = channels
+        .into_iter()
+        .filter_map(|channel| {
+            let id = channel.id?;
+            Some(NewChannel {
+                name: channel.name.unwrap_or_else(|| id.clone()),
+                xmltv_id: id,
+                icon: channel.icon,
+            })
+        })
+        .collect();
+    let mut guide = Guide {
+        channels,
+        ..Default::default()
+    };
+
+    programs.sort_by(|a, b| (&a.channel, a.starts_at).cmp(&(&b.channel, b.starts_at)));
+    for i in 1..programs.len() {
+        if programs[i - 1].ends_at.is_none() && programs[i - 1].channel == programs[i].channel {
+            programs[i - 1].ends_at = programs[i].starts_at;
+        }
+    }
+    for program in programs {
+        match (
+            program.channel,
+            program.starts_at,
+            program.ends_at,
+            program.title,
+        ) {
+            (Some(channel), Some(starts_at), Some(ends_at), Some(title)) if ends_at > starts_at => {
+                guide.programs.push(NewProgram {
+                    channel,
+                    starts_at,
+                    ends_at,
+                    title,
+                    subtitle: program.subtitle,
+                    description: program.description,
+                    category: program.category,
+                })
+            }
+            _ => guide.invalid += 1,
+        }
+    }
+
+    Ok(guide)
+}
+
+fn attribute(element: &BytesStart, name: &[u8]) -> Result<Option<String>, quick_xml::Error> {
+    match element.try_get_attribute(name)? {
+        Some(attribute) => Ok(Some(attribute.unescape_value()?.into_owned())),
+        None => Ok(None),
+    }
+}
+
+/// Programmes have icons too, only the channel's is kept.
+fn set_icon(
+    channel: &mut Option<PartialChannel>,
+    element: &BytesStart,
+) -> Result<(), quick_xml::Error> {
+    if let Some(channel) = channel {
+        if channel.icon.is_none() {
+            channel.icon = attribute(element, b"src")?;
+        }
+    }
+    Ok(())
+}
+
+fn text_slot<'a>(
+    field: Option<Field>,
+    channel: &'a mut Option<PartialChannel>,
+    program: &'a mut Option<PartialProgram>,
+) -> Option<&'a mut Option<String>> {
+    match (field?, channel, program) {
+        (Field::DisplayName, Some(channel), _) => Some(&mut channel.name),
+        (Field::Title, _, Some(program)) => Some(&mut program.title),
+        (Field::SubTitle, _, Some(program)) => Some(&mut program.subtitle),
+        (Field::Desc, _, Some(program)) => Some(&mut program.description),
+        (Field::Category, _, Some(program)) => Some(&mut program.category),
+        _ => None,
+    }
+}
+
+/// XMLTV times look like `20240101183000 +0100`, trailing fields of the
+/// time may be left out and a missing offset means UTC.
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    let (digits, offset) = match value.trim().split_once(' ') {
+        Some((digits, offset)) => (digits, offset.trim()),
+        None => (value.trim(), "+0000"),
+    };
+    if !(8..=14).contains(&digits.len()) || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+
+    let time = format!("{:0<14} {}", digits, offset);
+    DateTime::parse_from_str(&time, "%Y%m%d%H%M%S %z")
+        .ok()
+        .map(|time| time.with_timezone(&Utc))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeZone, Utc};
+
+    use super::{parse, parse_time};
+
+    const GUIDE: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<tv>
+  <channel id="one.example">
+    <display-name lang="de">Eins</display-name>
+    <display-name lang="en">One</display-name>
+    <icon src="https://example.com/one.png"/>
+  </channel>
+  <channel id="two.example"></channel>
+  <programme start="20240101180000 +0100" channel="one.example">
+    <title lang="de">Nachrichten</title>
+    <title lang="en">News</title>
+    <desc><![CDATA[Headlines & weather]]></desc>
+  </programme>
+  <programme start="20240101190000 +0100" stop="20240101200000 +0100" channel="one.example">
+    <title>Film</title>
+    <sub-title>Part 1</sub-title>
+    <category>Movie</category>
+    <icon src="https://example.com/film.png"/>
+  </programme>
+  <programme start="20240101230000" channel="two.example">
+    <title>No successor</title>
+  </programme>
+  <programme start="20240101200000" stop="20240101190000" channel="two.example">
+    <title>Ends before it starts</title>
+  </programme>
+  <programme start="20240101210000" stop="20240101220000" channel="two.example">
+  </programme>
+</tv>"#;
+
+    fn utc(hour: u32, min: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 1, 1, hour, min, 0).unwrap()
+    }
+
+    #[test]
+    fn parse_time_converts_to_utc() {
+        assert_eq!(parse_time("20240101183000 +0100"), Some(utc(17, 30)));
+        assert_eq!(parse_time("20240101183000 -0030"), Some(utc(19, 0)));
+        // a missing offset is UTC, missing trailing fields are zero
+        assert_eq!(parse_time("20240101183000"), Some(utc(18, 30)));
+        assert_eq!(parse_time("202401011830 +0000"), Some(utc(18, 30)));
+        assert_eq!(parse_time("20240101"), Some(utc(0, 0)));
+        assert_eq!(parse_time("2024"), None);
+        assert_eq!(parse_time("2024010118300x"), None);
+        assert_eq!(parse_time("20240101183000 CET"), None);
+    }
+
+    #[test]
+    fn parse_reads_channels_and_programmes() {
+        let guide = parse(GUIDE.as_bytes()).unwrap();
+
+        assert_eq!(guide.channels.len(), 2);
+        assert_eq!(guide.channels[0].xmltv_id, "one.example");
+        assert_eq!(guide.channels[0].name, "Eins");
+        assert_eq!(
+            guide.channels[0].icon.as_deref(),
+            Some("https://example.com/one.png")
+        );
+        // the id stands in for a missing display name
+        assert_eq!(guide.channels[1].name, "two.example");
+        assert_eq!(guide.channels[1].icon, None);
+
+        assert_eq!(guide.programs.len(), 2);
+        let news = &guide.programs[0];
+        assert_eq!(news.title, "Nachrichten");
+        assert_eq!(news.starts_at, utc(17, 0));
+        // filled in from the start of the next programme on the channel
+        assert_eq!(news.ends_at, utc(18, 0));
+        assert_eq!(news.description.as_deref(), Some("Headlines & weather"));
+
+        let film = &guide.programs[1];
+        assert_eq!(film.title, "Film");
+        assert_eq!(film.ends_at, utc(19, 0));
+        assert_eq!(film.subtitle.as_deref(), Some("Part 1"));
+        assert_eq!(film.category.as_deref(), Some("Movie"));
+
+        // no stop and nothing after it, stop before start, no title
+        assert_eq!(guide.invalid, 3);
+    }
+}